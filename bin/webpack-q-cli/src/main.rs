@@ -14,20 +14,37 @@
  * limitations under the License.
  */
 
+mod repl;
+mod serve;
+
 use clap::Parser;
 use std::borrow::Cow;
-use std::fs::{create_dir_all, File, OpenOptions};
-use std::io::{BufReader, BufWriter, Read};
+use std::fs::{create_dir_all, OpenOptions};
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
+
+use crate::repl::repl;
+use crate::serve::serve;
+use webpack_q::budgets::{check_budgets, Budgets};
+use webpack_q::compressed::CompressionEstimator;
+use webpack_q::duplicates::find_duplicate_packages;
 use webpack_q::graphs::ser;
 use webpack_q::graphs::ser::GraphSerialization;
+use webpack_q::meshed::graph::{graphml, graphviz, mermaid};
 use webpack_q::operations::{
-    describe_chunk, describe_entrypoints, display_entrypoints, paths_to_chunk, traverse_entry_chunk,
+    describe_chunk, describe_diagnostics, describe_entrypoints, diff_stats, display_entrypoints,
+    paths_to_chunk, traverse_all_entrypoints, traverse_entry_chunk, traverse_entry_chunks,
+    why_module, BuildSizes, Severity,
 };
 
 pub use webpack_q::prelude::*;
-use webpack_q::templating::write_html_files_to_directory;
-use webpack_q::webpack_stats::chunk::ChunkId;
+use webpack_q::query::select;
+use webpack_q::session::Session;
+use webpack_q::templating::{write_html_files_to_directory, write_single_html_file, Template};
+use webpack_q::treemap::{size_tree, TreeScope};
+use webpack_q::webpack_stats::chunk::{ChunkId, Chunks};
+use webpack_q::webpack_stats::entry::Entrypoint;
+use webpack_q::webpack_stats::load::{deserialize_any_version_with, LoadOptions, StatsFile};
 use webpack_q::webpack_stats::WebpackStats;
 
 #[derive(Parser)]
@@ -35,6 +52,25 @@ struct Args {
     stats_file: PathBuf,
     #[clap(short)]
     quiet: bool,
+    /// Name of the child compilation to operate on in multi-compiler builds.
+    /// See list-compilations
+    #[clap(long, global = true)]
+    compilation: Option<String>,
+    /// Do not load the source code of modules. Reduces memory usage for
+    /// stats files generated with `source: true`
+    #[clap(long, global = true)]
+    skip_source: bool,
+    /// Do not load the list of emitted assets
+    #[clap(long, global = true)]
+    skip_assets: bool,
+    /// Write html output as a single file with the data inlined, which opens
+    /// without a web server
+    #[clap(long, global = true)]
+    single_file: bool,
+    /// Estimate the gzip and brotli size of every module in exported graphs.
+    /// Slow for stats files generated with `source: true`
+    #[clap(long, global = true)]
+    compressed_sizes: bool,
     #[clap(subcommand)]
     command: Command,
 }
@@ -44,32 +80,133 @@ enum Command {
     /// other commands to traverse entrypoint
     #[clap(name = "list-entrypoints")]
     ListEntrypoints,
+    /// List the names of the child compilations of a multi-compiler build.
+    /// Pass a name to --compilation to target that compilation
+    #[clap(name = "list-compilations")]
+    ListCompilations,
     /// Show statistics and traversal of an entrypoint
     DescribeEntrypoint { entrypoint_name: String },
 
+    /// List the errors and warnings of the compilation grouped by module and
+    /// by entrypoint
+    Diagnostics {
+        /// Exit with a failure if a diagnostic's kind (e.g. ModuleNotFoundError)
+        /// is equal to, or its message contains, the pattern. Can be repeated
+        #[clap(long = "fail-on", value_name = "PATTERN")]
+        fail_on: Vec<String>,
+        /// Exit with a failure if the compilation reported any error
+        #[clap(long)]
+        fail_on_errors: bool,
+    },
     /// Show information about a specific chunk
-    DescribeChunk { chunk_id: u32 },
+    DescribeChunk { chunk_id: ChunkId },
+    /// Compare the stats file with the stats file of another build. Lists
+    /// added, removed and resized chunks, modules and assets and the change
+    /// in initial load size of every entrypoint.
+    /// e.g. webpack-q old.json diff new.json
+    Diff { new: PathBuf },
+    /// Check the build against the size budgets in a toml file. Exits with a
+    /// failure when a budget is exceeded
+    Check {
+        #[clap(long)]
+        budget: PathBuf,
+    },
+    /// List packages from node_modules that are bundled more than once, the
+    /// bytes wasted by the extra copies and the chunks and entrypoints that
+    /// carry each copy
+    Duplicates,
+    /// Select modules or chunks with a query and print them as a table or
+    /// json.
+    /// e.g. query 'modules where size > 50KiB and chunk in entry("app").initial'
+    Query {
+        query: String,
+        #[clap(short = 'f', long, value_enum, default_value_t = QueryOutput::Table)]
+        output_format: QueryOutput,
+    },
     /// From an entrypoint in list-entrypoints, output a full traversal of that entrypoint and output it
     #[clap(name = "traverse-entrypoint")]
     TraverseEntrypoint {
         entrypoint_name: String,
+        /// Output the graph of modules, or of the chunks loaded by the entrypoint
+        #[clap(long, value_enum, default_value_t = Level::Module)]
+        level: Level,
         #[clap(short = 'f', long, value_enum, default_value_t = Output::Json)]
         output_format: Output,
         #[clap(short = 'o', default_value = "webpack-q")]
         output_path: PathBuf,
     },
+    /// Traverse every entrypoint and output one graph per entrypoint into the
+    /// output directory. Runs in parallel with the `parallel` feature
+    #[clap(name = "traverse-all-entrypoints")]
+    TraverseAllEntrypoints {
+        #[clap(short = 'f', long, value_enum, default_value_t = Output::Json)]
+        output_format: Output,
+        #[clap(short = 'o', default_value = "webpack-q")]
+        output_path: PathBuf,
+    },
+    /// Break down module sizes by package, directory and file. Covers the
+    /// whole compilation unless a chunk or an entrypoint is given
+    Treemap {
+        #[clap(long, conflicts_with = "entrypoint")]
+        chunk: Option<ChunkId>,
+        #[clap(long)]
+        entrypoint: Option<String>,
+        /// Leave out the chunks the entrypoint loads asynchronously
+        #[clap(long, requires = "entrypoint")]
+        initial: bool,
+        #[clap(short = 'f', long, value_enum, default_value_t = Output::Html)]
+        output_format: Output,
+        #[clap(short = 'o', default_value = "webpack-q")]
+        output_path: PathBuf,
+    },
+    /// Explain why modules are in an entrypoint. Prints the shortest import
+    /// chain from the entry modules to every module whose name or identifier
+    /// contains the pattern.
+    /// e.g. why index node_modules/lodash
+    Why {
+        entrypoint_name: String,
+        module_pattern: String,
+        #[clap(short = 'f', long, value_enum, default_value_t = Output::Json)]
+        output_format: Output,
+        /// Also output the import chains as a graph
+        #[clap(short = 'o')]
+        output_path: Option<PathBuf>,
+    },
     /// Find all the possible ways that an entrypoint escapes into a target chunk.
     /// e.g. paths-to-chunk entry-chunk 6332
     /// where 6332 is your commons chunk
     #[clap(name = "paths-to-chunk")]
     PathsToChunk {
         entrypoint_name: String,
-        chunk: u32,
+        chunk: ChunkId,
         #[clap(short = 'f', value_enum, default_value_t = Output::Json)]
         output_format: Output,
         #[clap(short = 'o', default_value = "webpack-q")]
         output_path: PathBuf,
     },
+    /// Load the stats file once and answer queries over local http. Open the
+    /// address in a browser to list the entrypoints.
+    /// e.g. webpack-q stats.json serve
+    Serve {
+        #[clap(long, default_value = "127.0.0.1:8080")]
+        address: String,
+    },
+    /// Load the stats file once and run commands against it interactively.
+    /// Type help in the repl for the list of commands
+    /// e.g. webpack-q stats.json repl
+    Repl,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+enum Level {
+    Chunk,
+    Module,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+enum QueryOutput {
+    Table,
+    Json,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
@@ -77,37 +214,170 @@ enum Output {
     Json,
     Html,
     Dot,
+    Mermaid,
+    Graphml,
+}
+
+impl Output {
+    fn extension(&self) -> &'static str {
+        match self {
+            Output::Json => "json",
+            Output::Html => "html",
+            Output::Dot => "dot",
+            Output::Mermaid => "mmd",
+            Output::Graphml => "graphml",
+        }
+    }
+}
+
+/// Every supported stats version exposes the same field names. Expand the
+/// body once per version so that the operations can stay generic.
+macro_rules! with_stats {
+    ($stats:expr, $name:ident => $body:expr) => {
+        match $stats {
+            WebpackStats::V2($name) => $body,
+            WebpackStats::V3($name) => $body,
+            WebpackStats::V4($name) => $body,
+            WebpackStats::V5($name) => $body,
+            WebpackStats::Rspack($name) => $body,
+        }
+    };
 }
 
 fn main() -> anyhow::Result<()> {
     let args: Args = Args::parse();
 
     tracing_subscriber::fmt::init();
-    // assume v5 for now;
-    let contents = {
-        let mut file = OpenOptions::new().read(true).open(&args.stats_file)?;
-        let mut reader = BufReader::new(&mut file);
-        let mut string = String::new();
-        reader.read_to_string(&mut string)?;
-        string
+    let options = LoadOptions {
+        skip_source: args.skip_source,
+        skip_assets: args.skip_assets,
     };
 
-    let stats = webpack_q::webpack_stats::deserialize_any_version(&contents)?;
+    let stats_file = args.stats_file.as_path();
+    let file = StatsFile::open(stats_file)?;
+    let stats = deserialize_any_version_with(file.as_str()?, options)?;
+    let stats = select_compilation(stats, args.compilation.as_deref())?;
 
+    let single_file = args.single_file;
+    let compressed_sizes = args.compressed_sizes;
     match args.command {
         Command::ListEntrypoints => {
-            let entrypoints = match &stats {
-                WebpackStats::V5(stats) => stats.entrypoints.values().collect::<Vec<_>>(),
-            };
+            let display = with_stats!(&stats, stats => {
+                display_entrypoints(&stats.entrypoints.values().collect::<Vec<_>>()).to_string()
+            });
 
-            let display = display_entrypoints(&entrypoints);
             println!("{}", display);
         }
+        Command::ListCompilations => {
+            for name in stats.compilation_names() {
+                println!("{}", name);
+            }
+        }
+        Command::Diagnostics {
+            fail_on,
+            fail_on_errors,
+        } => {
+            let failures = with_stats!(&stats, stats => {
+                let entrypoints = stats.entrypoints.values().collect::<Vec<_>>();
+                let report = describe_diagnostics(
+                    &stats.errors,
+                    &stats.warnings,
+                    &entrypoints,
+                    &stats.chunks,
+                    &stats.modules,
+                );
+                println!("{}", report);
+
+                let mut failures = vec![];
+                if fail_on_errors && report.count(Severity::Error) > 0 {
+                    failures.push(format!("{} errors", report.count(Severity::Error)));
+                }
+                for pattern in fail_on.iter() {
+                    let matched = report.matching(pattern).count();
+                    if matched > 0 {
+                        failures.push(format!("{} diagnostics matching {:?}", matched, pattern));
+                    }
+                }
+                failures
+            });
+
+            if !failures.is_empty() {
+                anyhow::bail!("Found {}", failures.join(", "));
+            }
+        }
+        Command::Diff { new } => {
+            let new_file = StatsFile::open(new)?;
+            let new = deserialize_any_version_with(new_file.as_str()?, options)?;
+            let new = build_sizes(&select_compilation(new, args.compilation.as_deref())?);
+
+            print!("{}", diff_stats(&build_sizes(&stats), &new));
+        }
+        Command::Serve { address } => {
+            with_stats!(&stats, stats => {
+                let output_path = resolve_output_path(stats_file, &stats.output_path);
+                let estimator = CompressionEstimator::new(&output_path, &stats.modules)
+                    .with_module_estimates(compressed_sizes);
+                let session = Session::new(
+                    &stats.modules,
+                    &stats.chunks,
+                    stats.entrypoints.values(),
+                    estimator,
+                );
+                serve(&session, &address)?;
+            });
+        }
+        Command::Repl => {
+            with_stats!(&stats, stats => {
+                let output_path = resolve_output_path(stats_file, &stats.output_path);
+                let estimator = CompressionEstimator::new(&output_path, &stats.modules)
+                    .with_module_estimates(compressed_sizes);
+                let session = Session::new(
+                    &stats.modules,
+                    &stats.chunks,
+                    stats.entrypoints.values(),
+                    estimator,
+                );
+                repl(&session, single_file)?;
+            });
+        }
+        Command::Check { budget } => {
+            let budgets = Budgets::from_toml(&std::fs::read_to_string(&budget)?)?;
+            let report = with_stats!(&stats, stats => {
+                let entrypoints = stats.entrypoints.values().collect::<Vec<_>>();
+                check_budgets(&budgets, &entrypoints, &stats.assets, &stats.chunks)
+            });
+            print!("{}", report);
+
+            if !report.passed() {
+                anyhow::bail!("{} budgets exceeded", report.violations().count());
+            }
+        }
+        Command::Duplicates => {
+            let report = with_stats!(&stats, stats => {
+                let entrypoints = stats.entrypoints.values().collect::<Vec<_>>();
+                find_duplicate_packages(&entrypoints, &stats.modules, &stats.chunks)
+            });
+            print!("{}", report);
+        }
+        Command::Query {
+            query,
+            output_format,
+        } => {
+            let selected = with_stats!(&stats, stats => {
+                let entrypoints = stats.entrypoints.values().collect::<Vec<_>>();
+                select(&query, &entrypoints, &stats.modules, &stats.chunks)?
+            });
+            match output_format {
+                QueryOutput::Table => print!("{}", selected),
+                QueryOutput::Json => println!("{}", serde_json::to_string_pretty(&selected)?),
+            }
+        }
         Command::DescribeChunk { chunk_id } => {
-            let chunk_id = ChunkId(chunk_id);
-            let description = match stats {
-                WebpackStats::V5(stats) => describe_chunk(chunk_id, &stats.chunks, &stats.modules),
-            };
+            let description = with_stats!(stats, stats => {
+                let output_path = resolve_output_path(stats_file, &stats.output_path);
+                let estimator = CompressionEstimator::new(&output_path, &stats.modules);
+                describe_chunk(stats.chunks.resolve_id(chunk_id), &stats.chunks, &stats.modules, &estimator)
+            });
             if let Some(description) = description {
                 println!("{}", description);
             }
@@ -118,68 +388,253 @@ fn main() -> anyhow::Result<()> {
             output_path,
             output_format,
         } => {
-            let graph = match stats {
-                WebpackStats::V5(stats) => {
-                    let entrypoint = stats
-                        .entrypoints
-                        .get(&Cow::Owned(entrypoint_name))
-                        .ok_or(anyhow::anyhow!("Entrypoint does not exist"))?;
-
-                    let target_chunk = ChunkId(chunk);
-                    paths_to_chunk(entrypoint, target_chunk, &stats.chunks, &stats.modules)
-                }
-            };
-            output_graph(&output_path, &output_format, move |mut writer| {
-                let _ = match &output_format {
-                    Output::Json | Output::Html => {
-                        let serializable = GraphSerialization::<_, ser::NodeEdge>::new(graph);
-                        serde_json::to_writer_pretty(writer, &serializable);
-                    }
-                    Output::Dot => {
-                        dot::render(graph.inner(), &mut writer);
+            let graph = with_stats!(stats, stats => {
+                let entrypoint = stats
+                    .entrypoints
+                    .get(&Cow::Owned(entrypoint_name))
+                    .ok_or(anyhow::anyhow!("Entrypoint does not exist"))?;
+
+                let chunk = stats.chunks.resolve_id(chunk);
+                let graph = paths_to_chunk(entrypoint, chunk, &stats.chunks, &stats.modules);
+                let output_path = resolve_output_path(stats_file, &stats.output_path);
+                CompressionEstimator::new(&output_path, &stats.modules)
+                    .with_module_estimates(compressed_sizes)
+                    .annotate_modules(&graph, &stats.modules);
+                graph
+            });
+            write_output(
+                &output_path,
+                &output_format,
+                Template::Graph,
+                single_file,
+                move |mut writer| {
+                    match &output_format {
+                        Output::Json | Output::Html => {
+                            let serializable = GraphSerialization::<_, ser::NodeEdge>::new(graph);
+                            serde_json::to_writer_pretty(writer, &serializable)?;
+                        }
+                        Output::Dot => graphviz::render(graph.inner(), &mut writer)?,
+                        Output::Mermaid => mermaid::render(graph.inner(), &mut writer)?,
+                        Output::Graphml => graphml::render(graph.inner(), &mut writer)?,
+                    };
+                    Ok(())
+                },
+            )?;
+        }
+
+        Command::Treemap {
+            chunk,
+            entrypoint,
+            initial,
+            output_format,
+            output_path,
+        } => {
+            if !matches!(output_format, Output::Json | Output::Html) {
+                anyhow::bail!("Treemaps can only be output as json or html");
+            }
+
+            let tree = with_stats!(stats, stats => {
+                let chunk = chunk.map(|chunk| stats.chunks.resolve_id(chunk));
+                let (name, scope) = match (chunk, &entrypoint) {
+                    (Some(chunk), _) => (format!("chunk {}", chunk), TreeScope::Chunk(chunk)),
+                    (None, Some(name)) => {
+                        let entrypoint = stats
+                            .entrypoints
+                            .get(&Cow::Borrowed(name.as_str()))
+                            .ok_or(anyhow::anyhow!("Entrypoint does not exist"))?;
+                        let scope = TreeScope::Entrypoint {
+                            chunks: entrypoint.chunks(),
+                            initial_only: initial,
+                        };
+                        (name.clone(), scope)
                     }
+                    (None, None) => ("compilation".to_string(), TreeScope::Compilation),
                 };
-                Ok(())
-            })?;
+                size_tree(&name, scope, &stats.modules, &stats.chunks)
+            });
+
+            write_output(
+                &output_path,
+                &output_format,
+                Template::Treemap,
+                single_file,
+                move |writer| {
+                    serde_json::to_writer_pretty(writer, &tree)?;
+                    Ok(())
+                },
+            )?;
         }
+        Command::Why {
+            entrypoint_name,
+            module_pattern,
+            output_format,
+            output_path,
+        } => {
+            let inclusion = with_stats!(stats, stats => {
+                let entrypoint = stats
+                    .entrypoints
+                    .get(&Cow::Owned(entrypoint_name))
+                    .ok_or(anyhow::anyhow!("Entrypoint does not exist"))?;
+
+                let inclusion = why_module(entrypoint, &module_pattern, &stats.chunks, &stats.modules);
+                let output_path = resolve_output_path(stats_file, &stats.output_path);
+                CompressionEstimator::new(&output_path, &stats.modules)
+                    .with_module_estimates(compressed_sizes)
+                    .annotate_modules(&inclusion.graph, &stats.modules);
+                inclusion
+            });
+            print!("{}", inclusion);
 
+            if let Some(output_path) = output_path {
+                let graph = inclusion.graph;
+                write_output(
+                    &output_path,
+                    &output_format,
+                    Template::Graph,
+                    single_file,
+                    move |mut writer| {
+                        match &output_format {
+                            Output::Json | Output::Html => {
+                                let serializable =
+                                    GraphSerialization::<_, ser::NodeEdge>::new(graph);
+                                serde_json::to_writer_pretty(writer, &serializable)?;
+                            }
+                            Output::Dot => graphviz::render(graph.inner(), &mut writer)?,
+                            Output::Mermaid => mermaid::render(graph.inner(), &mut writer)?,
+                            Output::Graphml => graphml::render(graph.inner(), &mut writer)?,
+                        };
+                        Ok(())
+                    },
+                )?;
+            }
+        }
         Command::TraverseEntrypoint {
             entrypoint_name,
+            level: Level::Chunk,
             output_format,
             output_path,
         } => {
-            let graph = match stats {
-                WebpackStats::V5(stats) => {
-                    let entrypoint = stats
-                        .entrypoints
-                        .get(&Cow::Owned(entrypoint_name))
-                        .ok_or(anyhow::anyhow!("Entrypoint does not exist"))?;
-                    traverse_entry_chunk(stats.modules, stats.chunks, entrypoint)?
-                }
-            };
-            output_graph(&output_path, &output_format, move |mut writer| {
-                let _ = match &output_format {
-                    Output::Json | Output::Html => {
-                        let serializable = GraphSerialization::<_, ser::NodeEdge>::new(graph);
-                        serde_json::to_writer_pretty(writer, &serializable);
-                    }
-                    Output::Dot => {
-                        dot::render(graph.inner(), &mut writer);
-                    }
-                };
-                Ok(())
-            })?;
+            let graph = with_stats!(stats, stats => {
+                let entrypoint = stats
+                    .entrypoints
+                    .get(&Cow::Owned(entrypoint_name))
+                    .ok_or(anyhow::anyhow!("Entrypoint does not exist"))?;
+                traverse_entry_chunks(&stats.chunks, entrypoint)
+            });
+            write_output(
+                &output_path,
+                &output_format,
+                Template::Graph,
+                single_file,
+                move |mut writer| {
+                    match &output_format {
+                        Output::Json | Output::Html => {
+                            let serializable = GraphSerialization::<_, ser::NodeEdge>::new(graph);
+                            serde_json::to_writer_pretty(writer, &serializable)?;
+                        }
+                        Output::Dot => graphviz::render(&graph, &mut writer)?,
+                        Output::Mermaid => mermaid::render(&graph, &mut writer)?,
+                        Output::Graphml => graphml::render(&graph, &mut writer)?,
+                    };
+                    Ok(())
+                },
+            )?;
+        }
+        Command::TraverseEntrypoint {
+            entrypoint_name,
+            level: Level::Module,
+            output_format,
+            output_path,
+        } => {
+            let graph = with_stats!(stats, stats => {
+                let entrypoint = stats
+                    .entrypoints
+                    .get(&Cow::Owned(entrypoint_name))
+                    .ok_or(anyhow::anyhow!("Entrypoint does not exist"))?;
+                let graph = traverse_entry_chunk(&stats.modules, &stats.chunks, entrypoint)?;
+                let output_path = resolve_output_path(stats_file, &stats.output_path);
+                CompressionEstimator::new(&output_path, &stats.modules)
+                    .with_module_estimates(compressed_sizes)
+                    .annotate_modules(&graph, &stats.modules);
+                graph
+            });
+            write_output(
+                &output_path,
+                &output_format,
+                Template::Graph,
+                single_file,
+                move |mut writer| {
+                    match &output_format {
+                        Output::Json | Output::Html => {
+                            let serializable = GraphSerialization::<_, ser::NodeEdge>::new(graph);
+                            serde_json::to_writer_pretty(writer, &serializable)?;
+                        }
+                        Output::Dot => graphviz::render(graph.inner(), &mut writer)?,
+                        Output::Mermaid => mermaid::render(graph.inner(), &mut writer)?,
+                        Output::Graphml => graphml::render(graph.inner(), &mut writer)?,
+                    };
+                    Ok(())
+                },
+            )?;
+        }
+        Command::TraverseAllEntrypoints {
+            output_format,
+            output_path,
+        } => {
+            let graphs = with_stats!(&stats, stats => {
+                let entrypoints = stats.entrypoints.values().collect::<Vec<_>>();
+                let output_path = resolve_output_path(stats_file, &stats.output_path);
+                let estimator = CompressionEstimator::new(&output_path, &stats.modules)
+                    .with_module_estimates(compressed_sizes);
+                traverse_all_entrypoints(&stats.modules, &stats.chunks, &entrypoints)
+                    .into_iter()
+                    .filter_map(|(name, graph)| match graph {
+                        Ok(graph) => {
+                            estimator.annotate_modules(&graph, &stats.modules);
+                            Some((name.to_string(), graph))
+                        }
+                        Err(err) => {
+                            tracing::warn!("Entrypoint {}: {}. Skipping", name, err);
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            create_dir_all(&output_path)?;
+            for (name, graph) in graphs {
+                let output_path = entrypoint_output_path(&output_path, &name, &output_format);
+                write_output_file(
+                    &output_path,
+                    &output_format,
+                    Template::Graph,
+                    single_file,
+                    move |mut writer| {
+                        match &output_format {
+                            Output::Json | Output::Html => {
+                                let serializable =
+                                    GraphSerialization::<_, ser::NodeEdge>::new(graph);
+                                serde_json::to_writer_pretty(writer, &serializable)?;
+                            }
+                            Output::Dot => graphviz::render(graph.inner(), &mut writer)?,
+                            Output::Mermaid => mermaid::render(graph.inner(), &mut writer)?,
+                            Output::Graphml => graphml::render(graph.inner(), &mut writer)?,
+                        };
+                        Ok(())
+                    },
+                )?;
+            }
         }
         Command::DescribeEntrypoint {
             entrypoint_name, ..
         } => {
-            let description = match stats {
-                WebpackStats::V5(stats) => {
-                    let entrypoints = stats.entrypoints.values().collect::<Vec<_>>();
-                    let entries = display_entrypoints(&entrypoints);
-                    describe_entrypoints(stats.chunks, &entrypoint_name, entries)?
-                }
-            };
+            let description = with_stats!(stats, stats => {
+                let entrypoints = stats.entrypoints.values().collect::<Vec<_>>();
+                let entries = display_entrypoints(&entrypoints);
+                let output_path = resolve_output_path(stats_file, &stats.output_path);
+                let estimator = CompressionEstimator::new(&output_path, &stats.modules);
+                describe_entrypoints(&stats.chunks, &entrypoint_name, entries, &estimator)?
+            });
             println!("{}", description);
         }
     }
@@ -187,42 +642,96 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn output_graph(
+/// Replace the stats with the child compilation called `name`, if any
+fn select_compilation<'a>(
+    stats: WebpackStats<'a>,
+    name: Option<&str>,
+) -> anyhow::Result<WebpackStats<'a>> {
+    let name = match name {
+        None => return Ok(stats),
+        Some(name) => name,
+    };
+
+    let available = stats.compilation_names().join(", ");
+    stats.into_compilation(name).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Compilation {} does not exist. Available compilations: [{}]",
+            name,
+            available
+        )
+    })
+}
+
+/// Webpack reports an absolute output path, but allow paths relative to the
+/// stats file for stats that were moved or rewritten
+fn resolve_output_path(stats_file: &Path, output_path: &str) -> PathBuf {
+    let output_path = Path::new(output_path);
+    if output_path.as_os_str().is_empty() || output_path.is_absolute() {
+        return output_path.to_path_buf();
+    }
+
+    stats_file
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(output_path)
+}
+
+fn build_sizes(stats: &WebpackStats) -> BuildSizes {
+    with_stats!(stats, stats => {
+        let entrypoints = stats.entrypoints.values().collect::<Vec<_>>();
+        BuildSizes::new(&entrypoints, &stats.assets, &stats.chunks, &stats.modules)
+    })
+}
+
+fn write_output(
     output_path: &Path,
     output_format: &Output,
-    write: impl FnOnce(&mut BufWriter<&mut File>) -> std::io::Result<()>,
+    template: Template,
+    single_file: bool,
+    write: impl FnOnce(&mut dyn Write) -> std::io::Result<()>,
 ) -> anyhow::Result<()> {
-    let output_path = {
-        let mut output_path = output_path.to_path_buf();
-        match output_format {
-            Output::Json => {
-                output_path.set_extension("json");
-            }
-            Output::Html => {
-                output_path.set_extension("html");
-            }
-            Output::Dot => {
-                output_path.set_extension("dot");
-            }
-        };
+    let output_path = output_path.with_extension(output_format.extension());
+    write_output_file(&output_path, output_format, template, single_file, write)
+}
 
-        output_path
-    };
+/// The file an entrypoint is written to inside `directory`. Entrypoint names
+/// can contain dots, so the extension is appended instead of replacing
+/// whatever follows the last dot
+fn entrypoint_output_path(directory: &Path, name: &str, output_format: &Output) -> PathBuf {
+    let file_name = format!(
+        "{}.{}",
+        name.replace(['/', '\\'], "_"),
+        output_format.extension()
+    );
+    directory.join(file_name)
+}
 
+/// Like [`write_output`], with `output_path` used as is
+fn write_output_file(
+    output_path: &Path,
+    output_format: &Output,
+    template: Template,
+    single_file: bool,
+    write: impl FnOnce(&mut dyn Write) -> std::io::Result<()>,
+) -> anyhow::Result<()> {
     match output_format {
-        Output::Json | Output::Dot => {
+        Output::Json | Output::Dot | Output::Mermaid | Output::Graphml => {
             let mut logs_file = OpenOptions::new()
                 .write(true)
                 .create(true)
-                .open(&output_path)?;
+                .open(output_path)?;
             logs_file.set_len(0)?;
 
             let mut writer = BufWriter::new(&mut logs_file);
             write(&mut writer)?;
         }
+        Output::Html if single_file => {
+            write_single_html_file(output_path, template, write)?;
+            tracing::info!("Report outputted to {:?}", &output_path)
+        }
         Output::Html => {
-            create_dir_all(&output_path)?;
-            write_html_files_to_directory(&output_path, write)?;
+            create_dir_all(output_path)?;
+            write_html_files_to_directory(output_path, template, write)?;
             tracing::info!(
                 "Files outputted to {:?}. Open folder with a web server",
                 &output_path
@@ -232,3 +741,25 @@ fn output_graph(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entrypoint_names_keep_their_dots() {
+        let directory = Path::new("out");
+        assert_eq!(
+            entrypoint_output_path(directory, "app.v2", &Output::Json),
+            Path::new("out/app.v2.json")
+        );
+        assert_ne!(
+            entrypoint_output_path(directory, "a.b", &Output::Dot),
+            entrypoint_output_path(directory, "a.c", &Output::Dot)
+        );
+        assert_eq!(
+            entrypoint_output_path(directory, "pages/home", &Output::Html),
+            Path::new("out/pages_home.html")
+        );
+    }
+}
@@ -0,0 +1,297 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! `webpack-q repl`. Parses the stats file once, then answers commands until
+//! exit. Entrypoint names, chunk ids and module names tab complete, and the
+//! history is kept in `~/.webpack_q_history`.
+
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use webpack_q::graphs::ser::{GraphSerialization, NodeEdge};
+use webpack_q::graphs::ModuleParentGraph;
+use webpack_q::meshed::graph::{graphml, graphviz, mermaid, Inverted};
+use webpack_q::session::Session;
+use webpack_q::templating::Template;
+use webpack_q::webpack_stats::chunk::{Chunk, ChunkId, Chunks};
+use webpack_q::webpack_stats::entry::Entrypoint;
+use webpack_q::webpack_stats::module::{Module, Modules};
+
+use crate::{write_output, Output};
+
+const HELP: &str = "\
+entry                    list the entrypoints
+entry <name>             describe an entrypoint and select it
+chunk <id>               describe a chunk
+traverse                 every module of the selected entrypoint
+why <module>             import chains from the selected entrypoint to matching modules
+paths <entry> <chunk>    import paths from an entrypoint into a chunk
+export <format> [path]   write the last graph as json, html, dot, mermaid or graphml
+modules [where ...]      query the modules, e.g. modules where size > 50KiB
+chunks [where ...]       query the chunks, e.g. chunks where initial = false
+help                     show this message
+exit                     leave the repl";
+
+const COMMANDS: &[&str] = &[
+    "entry", "chunk", "traverse", "why", "paths", "export", "modules", "chunks", "help", "exit",
+];
+
+const FORMATS: &[&str] = &["json", "html", "dot", "mermaid", "graphml"];
+
+type ModuleGraph = GraphSerialization<Inverted<ModuleParentGraph>, NodeEdge>;
+
+/// Completion candidates, taken from the loaded stats
+struct ReplHelper {
+    entrypoints: Vec<String>,
+    chunks: Vec<String>,
+    modules: Vec<String>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let command = line.split_whitespace().next().unwrap_or_default();
+
+        // Module names contain spaces, so the rest of the line is the module
+        if command == "why" && line.len() > command.len() {
+            let start = line.len() - line[command.len()..].trim_start().len();
+            let word = &line[start..];
+            let candidates = self
+                .modules
+                .iter()
+                .filter(|module| module.contains(word))
+                .cloned()
+                .collect();
+            return Ok((start, candidates));
+        }
+
+        let start = line.rfind(' ').map(|index| index + 1).unwrap_or(0);
+        let word = &line[start..];
+        let arguments = line[..start].split_whitespace().count();
+        let candidates: Vec<&str> = match (command, arguments) {
+            (_, 0) => COMMANDS.to_vec(),
+            ("entry", 1) | ("paths", 1) => self.entrypoints.iter().map(String::as_str).collect(),
+            ("chunk", 1) | ("paths", 2) => self.chunks.iter().map(String::as_str).collect(),
+            ("export", 1) => FORMATS.to_vec(),
+            _ => vec![],
+        };
+        let candidates = candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .map(str::to_string)
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+struct ReplState {
+    entrypoint: Option<String>,
+    graph: Option<ModuleGraph>,
+}
+
+pub fn repl<M, Mv, C, Cv, E>(
+    session: &Session<M, Mv, C, Cv, E>,
+    single_file: bool,
+) -> anyhow::Result<()>
+where
+    M: Modules<Mv>,
+    Mv: Module,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+    E: Entrypoint,
+{
+    let mut modules: Vec<_> = session
+        .modules()
+        .all()
+        .into_iter()
+        .map(|module| module.label().to_string())
+        .collect();
+    modules.sort();
+    modules.dedup();
+    let mut chunks: Vec<_> = session
+        .chunks()
+        .all()
+        .into_iter()
+        .map(|chunk| chunk.get_id())
+        .collect();
+    chunks.sort();
+    let helper = ReplHelper {
+        entrypoints: session
+            .entrypoints()
+            .into_iter()
+            .map(|entrypoint| entrypoint.name)
+            .collect(),
+        chunks: chunks.iter().map(ToString::to_string).collect(),
+        modules,
+    };
+
+    let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(helper));
+    let history = std::env::var_os("HOME").map(|home| Path::new(&home).join(".webpack_q_history"));
+    if let Some(history) = &history {
+        // There is no history the first time around
+        let _ = editor.load_history(history);
+    }
+
+    println!("Type help for the list of commands");
+    let mut state = ReplState {
+        entrypoint: None,
+        graph: None,
+    };
+    loop {
+        let prompt = match &state.entrypoint {
+            Some(entrypoint) => format!("{}> ", entrypoint),
+            None => "> ".to_string(),
+        };
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(rustyline::error::ReadlineError::Interrupted) => continue,
+            Err(rustyline::error::ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line)?;
+
+        match run(session, &mut state, line, single_file) {
+            Ok(true) => break,
+            Ok(false) => {}
+            Err(err) => println!("error: {}", err),
+        }
+    }
+
+    if let Some(history) = &history {
+        if let Err(err) = editor.save_history(history) {
+            eprintln!("Could not save the history: {}", err);
+        }
+    }
+    Ok(())
+}
+
+/// Run a single command. Returns true when the repl should exit. Session
+/// errors are only thread safe with the `parallel` feature, so they are boxed
+/// instead of converted into anyhow errors
+fn run<M, Mv, C, Cv, E>(
+    session: &Session<M, Mv, C, Cv, E>,
+    state: &mut ReplState,
+    line: &str,
+    single_file: bool,
+) -> Result<bool, Box<dyn std::error::Error>>
+where
+    M: Modules<Mv>,
+    Mv: Module,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+    E: Entrypoint,
+{
+    let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let rest = rest.trim();
+    let arguments: Vec<&str> = rest.split_whitespace().collect();
+    let selected = || {
+        state
+            .entrypoint
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Select an entrypoint with entry <name> first"))
+    };
+
+    match (command, arguments.as_slice()) {
+        ("exit" | "quit", _) => return Ok(true),
+        ("help", _) => println!("{}", HELP),
+        ("entry", []) => {
+            for entrypoint in session.entrypoints() {
+                println!("{} ({})", entrypoint.name, entrypoint.initial_size);
+            }
+        }
+        ("entry", [name]) => {
+            println!("{}", session.describe_entrypoint(name)?);
+            state.entrypoint = Some(name.to_string());
+        }
+        ("chunk", [chunk]) => {
+            let chunk: ChunkId = chunk.parse()?;
+            print!("{}", session.describe_chunk(chunk)?);
+        }
+        ("traverse", []) => {
+            let graph = session.traverse_entrypoint(&selected()?)?;
+            println!("{} modules", graph.inner().all_nodes().count());
+            state.graph = Some(GraphSerialization::<_, NodeEdge>::new(graph));
+        }
+        ("why", [_, ..]) => {
+            let inclusion = session.why_module(&selected()?, rest)?;
+            print!("{}", inclusion);
+            state.graph = Some(GraphSerialization::<_, NodeEdge>::new(inclusion.graph));
+        }
+        ("paths", [entrypoint, chunk]) => {
+            let graph = session.paths_to_chunk(entrypoint, chunk.parse()?)?;
+            println!("{} modules", graph.inner().all_nodes().count());
+            state.graph = Some(GraphSerialization::<_, NodeEdge>::new(graph));
+        }
+        ("export", [format, path @ ..]) if path.len() <= 1 => {
+            let graph = state
+                .graph
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Nothing to export. Run traverse, why or paths"))?;
+            let format = Output::from_str(format, true).map_err(|err| anyhow::anyhow!(err))?;
+            let path = PathBuf::from(path.first().copied().unwrap_or("webpack-q"));
+            export(graph, format, &path, single_file)?;
+        }
+        ("modules" | "chunks", _) => print!("{}", session.select(line)?),
+        _ => println!("Unknown command. Type help for the list of commands"),
+    }
+
+    Ok(false)
+}
+
+fn export(
+    graph: &ModuleGraph,
+    format: Output,
+    path: &Path,
+    single_file: bool,
+) -> anyhow::Result<()> {
+    write_output(path, &format, Template::Graph, single_file, |mut writer| {
+        match format {
+            Output::Json | Output::Html => serde_json::to_writer_pretty(writer, graph)?,
+            Output::Dot => graphviz::render(graph.graph().inner(), &mut writer)?,
+            Output::Mermaid => mermaid::render(graph.graph().inner(), &mut writer)?,
+            Output::Graphml => graphml::render(graph.graph().inner(), &mut writer)?,
+        };
+        Ok(())
+    })
+}
@@ -0,0 +1,285 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! `webpack-q serve`. Answers queries about a compilation over local http,
+//! so that the stats file is only loaded once.
+//!
+//! Json endpoints:
+//! - `/api/entrypoints`
+//! - `/api/chunk?id=<chunk>`
+//! - `/api/traversal?entrypoint=<name>&level=<module|chunk>`
+//! - `/api/paths-to-chunk?entrypoint=<name>&chunk=<chunk>`
+//! - `/api/modules?search=<text>`
+//! - `/api/query?q=<query>`, see [`webpack_q::query`]
+//!
+//! Graphs can also be opened in the html view by replacing `/api/` with
+//! `/view/`.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Write};
+
+use serde::Serialize;
+use tiny_http::{Header, Response, Server, StatusCode};
+use webpack_q::graphs::ser;
+use webpack_q::graphs::ser::GraphSerialization;
+use webpack_q::query::QueryError;
+use webpack_q::session::{Session, SessionError};
+use webpack_q::templating::{write_single_html, Template};
+use webpack_q::webpack_stats::chunk::{Chunk, ChunkId, Chunks};
+use webpack_q::webpack_stats::entry::Entrypoint;
+use webpack_q::webpack_stats::module::{Module, Modules};
+
+type HttpResponse = Response<Cursor<Vec<u8>>>;
+
+pub fn serve<M, Mv, C, Cv, E>(
+    session: &Session<M, Mv, C, Cv, E>,
+    address: &str,
+) -> anyhow::Result<()>
+where
+    M: Modules<Mv>,
+    Mv: Module,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+    E: Entrypoint,
+{
+    let server = Server::http(address).map_err(|err| anyhow::anyhow!(err))?;
+    tracing::info!("Serving on http://{}", server.server_addr());
+
+    for request in server.incoming_requests() {
+        let (path, query) = parse_url(request.url());
+        tracing::info!("{} {}", request.method(), request.url());
+        let response = route(session, &path, &query).unwrap_or_else(|err| {
+            json_response(err.status, &HashMap::from([("error", err.message)]))
+        });
+        if let Err(err) = request.respond(response) {
+            tracing::warn!("Could not respond: {}", err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Sent back as `{"error": message}`
+struct HttpError {
+    status: u16,
+    message: String,
+}
+
+impl HttpError {
+    fn new(status: u16, message: impl ToString) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<SessionError> for HttpError {
+    fn from(err: SessionError) -> Self {
+        let status = match err {
+            SessionError::NoEntrypoint(_)
+            | SessionError::NoChunk(_)
+            | SessionError::Description(_)
+            | SessionError::Query(QueryError::NoEntrypoint(_)) => 404,
+            SessionError::Query(_) => 400,
+            SessionError::Traversal(_) => 500,
+        };
+        HttpError::new(status, err)
+    }
+}
+
+type RouteResult = Result<HttpResponse, HttpError>;
+
+fn route<M, Mv, C, Cv, E>(
+    session: &Session<M, Mv, C, Cv, E>,
+    path: &str,
+    query: &HashMap<String, String>,
+) -> RouteResult
+where
+    M: Modules<Mv>,
+    Mv: Module,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+    E: Entrypoint,
+{
+    let param = |name: &str| {
+        query
+            .get(name)
+            .map(String::as_str)
+            .ok_or_else(|| HttpError::new(400, format!("Missing query parameter {}", name)))
+    };
+    let chunk_param = |name: &str| param(name).map(parse_chunk);
+
+    let (view, endpoint) = match path.strip_prefix("/api/") {
+        Some(endpoint) => (false, endpoint),
+        None => match path.strip_prefix("/view/") {
+            Some(endpoint) => (true, endpoint),
+            None if path == "/" => return Ok(index(session)),
+            None => return Err(HttpError::new(404, format!("{} does not exist", path))),
+        },
+    };
+
+    match endpoint {
+        "entrypoints" if !view => Ok(json_response(200, &session.entrypoints())),
+        "chunk" if !view => Ok(json_response(
+            200,
+            &session.describe_chunk(chunk_param("id")?)?,
+        )),
+        "modules" if !view => Ok(json_response(
+            200,
+            &session.search_modules(param("search")?),
+        )),
+        "query" if !view => Ok(json_response(200, &session.select(param("q")?)?)),
+        "traversal" => {
+            let entrypoint = param("entrypoint")?;
+            if query.get("level").map(String::as_str) == Some("chunk") {
+                let graph = session.load_entry_chunks(entrypoint)?;
+                graph_response(view, &GraphSerialization::<_, ser::NodeEdge>::new(graph))
+            } else {
+                let graph = session.traverse_entrypoint(entrypoint)?;
+                graph_response(view, &GraphSerialization::<_, ser::NodeEdge>::new(graph))
+            }
+        }
+        "paths-to-chunk" => {
+            let graph = session.paths_to_chunk(param("entrypoint")?, chunk_param("chunk")?)?;
+            graph_response(view, &GraphSerialization::<_, ser::NodeEdge>::new(graph))
+        }
+        _ => Err(HttpError::new(404, format!("{} does not exist", path))),
+    }
+}
+
+fn write_json(writer: &mut dyn Write, value: &impl Serialize) -> std::io::Result<()> {
+    serde_json::to_writer(writer, value)?;
+    Ok(())
+}
+
+fn parse_chunk(value: &str) -> ChunkId {
+    match value.parse() {
+        Ok(chunk) => chunk,
+        Err(never) => match never {},
+    }
+}
+
+fn response(status: u16, body: Vec<u8>, content_type: &str) -> HttpResponse {
+    let header = Header::from_bytes("Content-Type", content_type).expect("Valid header");
+    Response::from_data(body)
+        .with_status_code(StatusCode(status))
+        .with_header(header)
+}
+
+fn json_response(status: u16, value: &impl Serialize) -> HttpResponse {
+    let body = serde_json::to_vec(value).expect("Responses serialize to json");
+    response(status, body, "application/json")
+}
+
+/// Graphs are json, or the graph view with the json inlined into it
+fn graph_response(view: bool, graph: &impl Serialize) -> RouteResult {
+    if !view {
+        return Ok(json_response(200, graph));
+    }
+
+    let mut body = vec![];
+    write_single_html(&mut body, Template::Graph, |writer| {
+        write_json(writer, graph)
+    })
+    .map_err(|err| HttpError::new(500, err))?;
+    Ok(response(200, body, "text/html; charset=utf-8"))
+}
+
+/// Links to the views and endpoints of every entrypoint
+fn index<M, Mv, C, Cv, E>(session: &Session<M, Mv, C, Cv, E>) -> HttpResponse
+where
+    M: Modules<Mv>,
+    Mv: Module,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+    E: Entrypoint,
+{
+    let mut body = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head><meta charset=\"UTF-8\"><title>webpack-q</title></head>\n<body>\n<h1>Entrypoints</h1>\n<ul>\n",
+    );
+    for entrypoint in session.entrypoints() {
+        let name = escape_html(&entrypoint.name);
+        let query = format!("entrypoint={}", encode(&entrypoint.name));
+        body.push_str(&format!(
+            "<li>{} ({}): <a href=\"/view/traversal?{q}\">modules</a>, \
+             <a href=\"/view/traversal?{q}&amp;level=chunk\">chunks</a>, \
+             <a href=\"/api/traversal?{q}\">json</a></li>\n",
+            name,
+            entrypoint.initial_size,
+            q = query
+        ));
+    }
+    body.push_str("</ul>\n</body>\n</html>\n");
+    response(200, body.into_bytes(), "text/html; charset=utf-8")
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Percent encode everything but unreserved characters
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = bytes
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (escaped, bytes[index]) {
+            (Some(byte), _) => {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+            (None, b'+') => decoded.push(b' '),
+            (None, byte) => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_url(url: &str) -> (String, HashMap<String, String>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect();
+    (decode(path), query)
+}
@@ -15,43 +15,43 @@
  */
 
 use std::any::{Any, TypeId};
-use std::cell::Cell;
 use std::collections::HashMap;
 
+use crate::rc::{Shareable, SharedCell};
+
+#[cfg(not(feature = "sync"))]
+type AnyValue = dyn Any;
+#[cfg(feature = "sync")]
+type AnyValue = dyn Any + Send + Sync;
+
 #[derive(Default)]
-pub struct AnymapCell(Cell<Anymap>);
+pub struct AnymapCell(SharedCell<Anymap>);
 
 impl AnymapCell {
-    pub fn insert<A: 'static>(&self, value: A) -> Option<A> {
-        let mut map = self.0.take();
-        let output = map.insert(value);
-        self.0.set(map);
-        output
+    pub fn insert<A: Shareable>(&self, value: A) -> Option<A> {
+        self.0.with_mut(|map| map.insert(value))
     }
 
-    pub fn get<A: Clone + 'static>(&self) -> Option<A> {
-        let mut map = self.0.take();
-        let output = map.get();
-        self.0.set(map);
-        output
+    pub fn get<A: Clone + Shareable>(&self) -> Option<A> {
+        self.0.with_mut(|map| map.get())
     }
 }
 
 #[derive(Default)]
-pub struct Anymap(HashMap<TypeId, Box<dyn Any>>);
+pub struct Anymap(HashMap<TypeId, Box<AnyValue>>);
 
 impl Anymap {
-    pub fn insert<A: 'static>(&mut self, value: A) -> Option<A> {
+    pub fn insert<A: Shareable>(&mut self, value: A) -> Option<A> {
         let annotations = &mut self.0;
         let type_id = value.type_id();
 
         let anno = annotations.remove(&type_id);
-        annotations.insert(type_id, Box::new(value) as Box<dyn Any>);
+        annotations.insert(type_id, Box::new(value) as Box<AnyValue>);
 
         anno.map(|d| *(d.downcast::<A>().unwrap()))
     }
 
-    pub fn remove<A: 'static>(&mut self) -> Option<A> {
+    pub fn remove<A: Shareable>(&mut self) -> Option<A> {
         let annotations = &mut self.0;
         let type_id = TypeId::of::<A>();
 
@@ -60,7 +60,7 @@ impl Anymap {
         anno.map(|d| *(d.downcast::<A>().unwrap()))
     }
 
-    pub fn get<A: Clone + 'static>(&mut self) -> Option<A> {
+    pub fn get<A: Clone + Shareable>(&mut self) -> Option<A> {
         let item: A = self.remove()?;
         let cloned = item.clone();
         self.insert(cloned);
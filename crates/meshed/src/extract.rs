@@ -15,11 +15,11 @@
  */
 
 use crate::identify::{Identifiable, Identity};
+use crate::rc::RefCount;
 
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::marker::PhantomData;
-use std::rc::Rc;
 
 /// Be able to query some type given an identifier. It must return a type that
 /// can be queried for more values like itself.
@@ -62,7 +62,7 @@ pub struct Edge<I: Identity, Meta> {
     pub source: I,
     pub sink: I,
     pub order: usize,
-    pub meta: Rc<Meta>,
+    pub meta: RefCount<Meta>,
 }
 
 impl<I: Identity, M> Clone for Edge<I, M> {
@@ -71,7 +71,7 @@ impl<I: Identity, M> Clone for Edge<I, M> {
             source: self.source.clone(),
             sink: self.sink.clone(),
             order: self.order,
-            meta: Rc::clone(&self.meta),
+            meta: RefCount::clone(&self.meta),
         }
     }
 }
@@ -85,7 +85,7 @@ where
             source,
             sink,
             order,
-            meta: Rc::new(meta),
+            meta: RefCount::new(meta),
         }
     }
 }
@@ -20,15 +20,20 @@ use edge::Edge;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 
-use dot::{Id, Nodes};
-use std::rc::Rc;
+use crate::rc::RefCount;
 
 use crate::extract::{Edges, ExtractData, Label, Query};
 
+use crate::graph::arena::ArenaGraph;
 use crate::graph::traversal::GraphTraversal;
 use crate::identify::{Identifiable, Identity};
 
+pub mod arena;
+pub mod attributes;
 pub mod edge;
+pub mod graphml;
+pub mod graphviz;
+pub mod mermaid;
 pub mod node;
 
 pub mod traversal;
@@ -114,6 +119,20 @@ pub trait GraphDefinition {
         }
         output.into_values().collect()
     }
+
+    /// Same as [`GraphDefinition::build_graph`] but stores the graph in index
+    /// based arenas. See [`ArenaGraph`]
+    fn build_arena<V, Q>(source: &Q) -> ArenaGraph<Self>
+    where
+        Q: Query<Self::Id, V>,
+        V: Identifiable<Self::Id>
+            + Edges<Self::Id, Self::EdgeMeta>
+            + ExtractData<Self::NodeData>
+            + Label<Label = Self::Label>,
+        Self: Sized,
+    {
+        ArenaGraph::build(source)
+    }
 }
 
 pub struct SimpleGraphDefinition;
@@ -280,12 +299,14 @@ impl Graph<SimpleGraphDefinition> {
             .entry(to.clone())
             .or_insert_with(|| Node::<SimpleGraphDefinition>::new_bare(to))
             .clone();
-        node.insert_edge(target, Rc::new(()));
+        node.insert_edge(target, RefCount::new(()));
     }
 }
 
 #[cfg(test)]
 mod test {
+    // Only used by the parallel traversal test
+    #[cfg_attr(not(feature = "sync"), allow(dead_code))]
     type SimpleGraph = Graph<SimpleGraphDefinition>;
 
     use crate::graph::node::Node;
@@ -448,32 +469,40 @@ mod test {
         );
         assert_eq!(node_edge_compare(&alt_one), vec![(4)]);
     }
-}
-impl<'a, T: GraphDefinition> dot::GraphWalk<'a, Node<T>, Edge<T>> for ConcreteGraph<T> {
-    fn nodes(&'a self) -> Nodes<'a, Node<T>> {
-        let nodes = self.all_nodes();
-        Nodes::Owned(nodes.collect())
-    }
 
-    fn edges(&'a self) -> dot::Edges<'a, Edge<T>> {
-        dot::Edges::Owned(self.all_edges().collect())
-    }
-
-    fn source(&'a self, edge: &Edge<T>) -> Node<T> {
-        edge.origin.clone()
-    }
-
-    fn target(&'a self, edge: &Edge<T>) -> Node<T> {
-        edge.target.clone()
-    }
-}
+    #[cfg(feature = "sync")]
+    #[test]
+    fn graph_can_be_traversed_in_parallel() {
+        use crate::graph::traversal::{traverse_graph, Instruction, Mode};
 
-impl<'a, T: GraphDefinition> dot::Labeller<'a, Node<T>, Edge<T>> for ConcreteGraph<T> {
-    fn graph_id(&'a self) -> Id<'a> {
-        Id::new("webpack_stats").unwrap()
-    }
+        let mut graph = SimpleGraph::default();
+        for (from, to) in [(1, 2), (1, 3), (2, 4), (3, 4), (4, 1)] {
+            graph.insert_edge(from, to);
+        }
 
-    fn node_id(&'a self, n: &Node<T>) -> Id<'a> {
-        Id::new(n.get_id().escaped()).unwrap()
+        let visited: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (1..=4)
+                .map(|id| {
+                    let node = graph.query(&id).cloned().unwrap();
+                    scope.spawn(move || {
+                        node.annotate(id);
+                        traverse_graph(node)
+                            .set_mode(Mode::Acyclic)
+                            .execute(|_, edge| {
+                                edge.target.annotate(id);
+                                Instruction::Continue(())
+                            })
+                            .nodes
+                            .len()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert_eq!(visited, vec![4, 4, 4, 4]);
+        assert!(graph
+            .all_nodes()
+            .all(|node| node.get_annotation::<i32>().is_some()));
     }
 }
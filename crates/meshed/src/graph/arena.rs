@@ -0,0 +1,587 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Index based graph storage.
+//!
+//! An [`ArenaGraph`] stores the id, label and data of every node once, in a
+//! table that is shared with the graphs derived from it. Adjacency is kept as
+//! compressed sparse rows: the outgoing edges of node `n` are
+//! `adjacency[offsets[n]..offsets[n + 1]]`. Nodes and edges are copyable
+//! handles into the graph, so inverting or projecting a graph only allocates
+//! a new adjacency.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+use crate::anymap::AnymapCell;
+use crate::extract::{Edges, ExtractData, Label, Query};
+use crate::graph::node::Node;
+use crate::graph::traversal::{TraversableEdge, TraversableNode, TraversalLog};
+use crate::graph::{Graph, GraphDefinition};
+use crate::identify::{Identifiable, Identity};
+use crate::rc::{RefCount, Shareable};
+
+type Index = u32;
+
+struct NodeTable<T: GraphDefinition> {
+    ids: Vec<T::Id>,
+    labels: Vec<RefCount<T::Label>>,
+    data: Vec<RefCount<T::NodeData>>,
+    edge_meta: Vec<RefCount<T::EdgeMeta>>,
+    lookup: HashMap<T::Id, Index>,
+}
+
+impl<T: GraphDefinition> NodeTable<T> {
+    fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    fn insert<V, Q>(&mut self, identity: &T::Id, source: &Q) -> Index
+    where
+        Q: Query<T::Id, V>,
+        V: Identifiable<T::Id> + ExtractData<T::NodeData> + Label<Label = T::Label>,
+    {
+        if let Some(index) = self.lookup.get(identity) {
+            return *index;
+        }
+
+        let value = source
+            .query(identity)
+            .unwrap_or_else(|| panic!("Created node that does not exist {}", identity));
+        let index = Index::try_from(self.ids.len()).expect("Graph exceeds u32::MAX nodes");
+        self.ids.push(identity.clone());
+        self.labels.push(RefCount::new(value.label()));
+        self.data.push(RefCount::new(value.extract_data()));
+        self.lookup.insert(identity.clone(), index);
+        index
+    }
+}
+
+/// A graph stored in index based arenas. See the [module docs](self).
+///
+/// Built with [`GraphDefinition::build_arena`] and traversed with the same
+/// [`traverse_graph`](crate::graph::traversal::traverse_graph) API as
+/// [`Graph`](crate::graph::Graph).
+pub struct ArenaGraph<T: GraphDefinition> {
+    table: RefCount<NodeTable<T>>,
+    annotations: RefCount<Vec<AnymapCell>>,
+    /// Nodes of the table that are part of this graph
+    members: Vec<bool>,
+    order: usize,
+    offsets: Vec<Index>,
+    /// (target, edge meta) of every edge, grouped by origin
+    adjacency: Vec<(Index, Index)>,
+}
+
+impl<T: GraphDefinition> ArenaGraph<T> {
+    pub fn build<V, Q>(source: &Q) -> Self
+    where
+        Q: Query<T::Id, V>,
+        V: Identifiable<T::Id>
+            + Edges<T::Id, T::EdgeMeta>
+            + ExtractData<T::NodeData>
+            + Label<Label = T::Label>,
+    {
+        let source = source.create_index();
+
+        let mut table = NodeTable {
+            ids: vec![],
+            labels: vec![],
+            data: vec![],
+            edge_meta: vec![],
+            lookup: HashMap::new(),
+        };
+        let mut edges = vec![];
+        let mut visited = vec![];
+
+        let mut queue: Vec<T::Id> = source
+            .all()
+            .into_iter()
+            .rev()
+            .map(|value| value.get_id())
+            .collect();
+
+        while let Some(identity) = queue.pop() {
+            let origin = table.insert(&identity, &source);
+            visited.resize(table.len(), false);
+            if visited[origin as usize] {
+                continue;
+            }
+            visited[origin as usize] = true;
+
+            let value: &V = source
+                .query(&identity)
+                .expect("Created a value that doesn't exist in the set");
+
+            for edge in value.edges() {
+                let target = table.insert(&edge.sink, &source);
+                let meta =
+                    Index::try_from(table.edge_meta.len()).expect("Graph exceeds u32::MAX edges");
+                table.edge_meta.push(edge.meta);
+                edges.push((origin, target, meta));
+                queue.push(edge.sink);
+            }
+        }
+
+        let members = vec![true; table.len()];
+        let annotations = (0..table.len()).map(|_| AnymapCell::default()).collect();
+        Self::from_edges(
+            RefCount::new(table),
+            RefCount::new(annotations),
+            members,
+            &edges,
+        )
+    }
+
+    /// Lay out (origin, target, meta) triples as compressed sparse rows.
+    /// Edges keep their relative order within an origin.
+    fn from_edges(
+        table: RefCount<NodeTable<T>>,
+        annotations: RefCount<Vec<AnymapCell>>,
+        members: Vec<bool>,
+        edges: &[(Index, Index, Index)],
+    ) -> Self {
+        let mut offsets = vec![0 as Index; table.len() + 1];
+        for (origin, _, _) in edges {
+            offsets[*origin as usize + 1] += 1;
+        }
+        for index in 0..table.len() {
+            offsets[index + 1] += offsets[index];
+        }
+
+        let mut cursor = offsets.clone();
+        let mut adjacency = vec![(0, 0); edges.len()];
+        for (origin, target, meta) in edges {
+            let slot = &mut cursor[*origin as usize];
+            adjacency[*slot as usize] = (*target, *meta);
+            *slot += 1;
+        }
+
+        Self {
+            order: members.iter().filter(|member| **member).count(),
+            table,
+            annotations,
+            members,
+            offsets,
+            adjacency,
+        }
+    }
+
+    fn handle(&self, index: Index) -> ArenaNode<'_, T> {
+        ArenaNode { graph: self, index }
+    }
+
+    pub fn node(&self, identity: &T::Id) -> Option<ArenaNode<'_, T>> {
+        let index = *self.table.lookup.get(identity)?;
+        self.members[index as usize].then(|| self.handle(index))
+    }
+
+    pub fn all_nodes(&self) -> impl Iterator<Item = ArenaNode<'_, T>> + '_ {
+        (0..self.members.len())
+            .filter(|index| self.members[*index])
+            .map(|index| self.handle(index as Index))
+    }
+
+    // all of the edges in the entire graph
+    pub fn all_edges(&self) -> impl Iterator<Item = ArenaEdge<'_, T>> + '_ {
+        self.all_nodes().flat_map(|node| node.edges())
+    }
+
+    /// Number of nodes
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Number of edges
+    pub fn size(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// Makes every child point to it's parent and vv. Like
+    /// [`Graph::invert`](crate::graph::Graph::invert) annotations are not
+    /// preserved.
+    pub fn invert(&self) -> Self {
+        let edges: Vec<_> = self
+            .all_edges()
+            .map(|edge| (edge.target.index, edge.origin.index, edge.meta_index))
+            .collect();
+        let annotations = (0..self.table.len())
+            .map(|_| AnymapCell::default())
+            .collect();
+
+        Self::from_edges(
+            RefCount::clone(&self.table),
+            RefCount::new(annotations),
+            self.members.clone(),
+            &edges,
+        )
+    }
+}
+
+impl<I: Identity> TraversalLog<I> {
+    /// Same as [`TraversalLog::project_into_graph`] for an [`ArenaGraph`].
+    /// The projection shares node data and annotations with `graph`.
+    pub fn project_into_arena<G>(&self, graph: &ArenaGraph<G>) -> ArenaGraph<G>
+    where
+        G: GraphDefinition<Id = I>,
+    {
+        let ids = &graph.table.ids;
+        let members = graph
+            .members
+            .iter()
+            .zip(ids.iter())
+            .map(|(member, identity)| *member && self.nodes.contains(identity))
+            .collect();
+
+        let edges: Vec<_> = graph
+            .all_edges()
+            .filter(|edge| {
+                let origin = &ids[edge.origin.index as usize];
+                let target = &ids[edge.target.index as usize];
+                self.edges.contains(&(origin.clone(), target.clone()))
+            })
+            .map(|edge| (edge.origin.index, edge.target.index, edge.meta_index))
+            .collect();
+
+        ArenaGraph::from_edges(
+            RefCount::clone(&graph.table),
+            RefCount::clone(&graph.annotations),
+            members,
+            &edges,
+        )
+    }
+
+    /// Copy the nodes and edges of the traversal out of an [`ArenaGraph`] into
+    /// a [`Graph`]. Labels, node data and edge meta are shared with `graph`,
+    /// annotations are not.
+    pub fn extract_from_arena<G>(&self, graph: &ArenaGraph<G>) -> Graph<G>
+    where
+        G: GraphDefinition<Id = I>,
+    {
+        let table = &graph.table;
+        let nodes: HashMap<Index, Node<G>> = graph
+            .all_nodes()
+            .filter(|node| self.nodes.contains(&table.ids[node.index as usize]))
+            .map(|node| {
+                let index = node.index as usize;
+                let node_value = Node::new_shared(
+                    table.ids[index].clone(),
+                    RefCount::clone(&table.labels[index]),
+                    RefCount::clone(&table.data[index]),
+                );
+                (node.index, node_value)
+            })
+            .collect();
+
+        for edge in graph.all_edges() {
+            if let (Some(origin), Some(target)) =
+                (nodes.get(&edge.origin.index), nodes.get(&edge.target.index))
+            {
+                if self.edges.contains(&(origin.get_id(), target.get_id())) {
+                    let meta = RefCount::clone(&table.edge_meta[edge.meta_index as usize]);
+                    origin.insert_edge(target.clone(), meta);
+                }
+            }
+        }
+
+        nodes.into_values().collect()
+    }
+}
+
+/// A node of an [`ArenaGraph`]
+pub struct ArenaNode<'g, T: GraphDefinition> {
+    graph: &'g ArenaGraph<T>,
+    index: Index,
+}
+
+impl<'g, T: GraphDefinition> ArenaNode<'g, T> {
+    pub fn label(&self) -> &'g T::Label {
+        self.graph.table.labels[self.index as usize].as_ref()
+    }
+
+    pub fn node_data(&self) -> &'g T::NodeData {
+        self.graph.table.data[self.index as usize].as_ref()
+    }
+
+    pub fn annotate<A: Shareable>(&self, value: A) -> Option<A> {
+        self.graph.annotations[self.index as usize].insert(value)
+    }
+
+    pub fn get_annotation<A: Clone + Shareable>(&self) -> Option<A> {
+        self.graph.annotations[self.index as usize].get()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = ArenaEdge<'g, T>> + 'g {
+        let origin = *self;
+        let start = self.graph.offsets[self.index as usize] as usize;
+        let end = self.graph.offsets[self.index as usize + 1] as usize;
+        self.graph.adjacency[start..end]
+            .iter()
+            .map(move |(target, meta)| ArenaEdge {
+                origin,
+                target: origin.graph.handle(*target),
+                meta_index: *meta,
+            })
+    }
+
+    pub fn get_edges(&self) -> Vec<ArenaEdge<'g, T>> {
+        self.edges().collect()
+    }
+
+    pub fn get_edge(&self, id: &T::Id) -> Option<ArenaEdge<'g, T>> {
+        self.edges().find(|edge| &edge.target.get_id() == id)
+    }
+}
+
+impl<'g, T: GraphDefinition> Clone for ArenaNode<'g, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'g, T: GraphDefinition> Copy for ArenaNode<'g, T> {}
+
+impl<'g, T: GraphDefinition> Identifiable<T::Id> for ArenaNode<'g, T> {
+    fn get_id(&self) -> T::Id {
+        self.graph.table.ids[self.index as usize].clone()
+    }
+}
+
+impl<'g, T: GraphDefinition> Debug for ArenaNode<'g, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let edge_list: Vec<_> = self
+            .edges()
+            .map(|edge| edge.target.get_id().to_string())
+            .collect();
+        write!(f, "[[ {:?} | [{}] ]]", self.get_id(), edge_list.join(", "))
+    }
+}
+
+impl<'g, T: GraphDefinition> TraversableNode for ArenaNode<'g, T> {
+    type Id = T::Id;
+    type Edge = ArenaEdge<'g, T>;
+
+    fn node_id(&self) -> Self::Id {
+        self.get_id()
+    }
+
+    fn for_each_edge<F: FnMut(Self::Edge)>(&self, visit: F) {
+        self.edges().for_each(visit)
+    }
+}
+
+/// An edge of an [`ArenaGraph`]
+pub struct ArenaEdge<'g, T: GraphDefinition> {
+    pub origin: ArenaNode<'g, T>,
+    pub target: ArenaNode<'g, T>,
+    meta_index: Index,
+}
+
+impl<'g, T: GraphDefinition> ArenaEdge<'g, T> {
+    pub fn meta(&self) -> &'g T::EdgeMeta {
+        &self.origin.graph.table.edge_meta[self.meta_index as usize]
+    }
+}
+
+impl<'g, T: GraphDefinition> Clone for ArenaEdge<'g, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'g, T: GraphDefinition> Copy for ArenaEdge<'g, T> {}
+
+impl<'g, T: GraphDefinition> TraversableEdge for ArenaEdge<'g, T> {
+    type Node = ArenaNode<'g, T>;
+
+    fn origin_node(&self) -> &Self::Node {
+        &self.origin
+    }
+
+    fn target_node(&self) -> &Self::Node {
+        &self.target
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::extract::{Edge, Edges, Label, Query};
+    use crate::graph::traversal::{traverse_graph, Instruction, Mode};
+    use crate::graph::{GraphDefinition, SimpleGraphDefinition};
+    use crate::identify::Identifiable;
+
+    type Id = i32;
+
+    struct Datastore {
+        store: Vec<Data>,
+    }
+
+    struct Data {
+        id: Id,
+        edges: Vec<Id>,
+    }
+
+    impl Label for Data {
+        type Label = Id;
+        fn label(&self) -> Self::Label {
+            self.id
+        }
+    }
+
+    impl Identifiable<Id> for Data {
+        fn get_id(&self) -> Id {
+            self.id
+        }
+    }
+
+    impl Query<Id, Data> for Datastore {
+        fn query(&self, identifier: &Id) -> Option<&Data> {
+            self.store.iter().find(|data| data.id == *identifier)
+        }
+
+        fn all(&self) -> Vec<&Data> {
+            self.store.iter().collect()
+        }
+    }
+
+    impl Edges<Id, ()> for Data {
+        fn next_edge(&self, previous_edge_index: Option<usize>) -> Option<Edge<Id, ()>> {
+            let next_idx = previous_edge_index.map(|e| e + 1).unwrap_or_default();
+            let edge = self.edges.get(next_idx)?;
+            Some(Edge::new(self.get_id(), *edge, next_idx, ()))
+        }
+    }
+
+    fn store() -> Datastore {
+        let data = |id, edges| Data { id, edges };
+        Datastore {
+            store: vec![
+                data(1, vec![2, 3]),
+                data(2, vec![4]),
+                data(3, vec![4]),
+                data(4, vec![1]),
+                data(5, vec![1]),
+            ],
+        }
+    }
+
+    fn targets<'g>(
+        edges: impl Iterator<Item = super::ArenaEdge<'g, SimpleGraphDefinition>>,
+    ) -> Vec<Id> {
+        edges.map(|edge| edge.target.get_id()).collect()
+    }
+
+    #[test]
+    fn arena_can_be_linked_together() {
+        let graph = SimpleGraphDefinition::build_arena(&store());
+
+        assert_eq!(graph.order(), 5);
+        assert_eq!(graph.size(), 6);
+
+        let one = graph.node(&1).unwrap();
+        assert_eq!(targets(one.edges()), vec![2, 3]);
+        let three = one.get_edge(&3).unwrap().target;
+        assert_eq!(targets(three.edges()), vec![4]);
+        let four = three.edges().next().unwrap().target;
+        assert_eq!(targets(four.edges()), vec![1]);
+        assert!(graph.node(&6).is_none());
+    }
+
+    #[test]
+    fn arena_can_be_inverted() {
+        let graph = SimpleGraphDefinition::build_arena(&store());
+        graph.node(&1).unwrap().annotate("annotation");
+        let graph = graph.invert();
+
+        assert_eq!(graph.order(), 5);
+        assert_eq!(graph.size(), 6);
+
+        let one = graph.node(&1).unwrap();
+        assert_eq!(
+            targets(one.edges()).into_iter().collect::<HashSet<_>>(),
+            HashSet::from([4, 5])
+        );
+        let four = graph.node(&4).unwrap();
+        assert_eq!(
+            targets(four.edges()).into_iter().collect::<HashSet<_>>(),
+            HashSet::from([2, 3])
+        );
+        assert!(graph.node(&5).unwrap().edges().next().is_none());
+        assert_eq!(one.get_annotation::<&str>(), None);
+    }
+
+    #[test]
+    fn arena_traversal_matches_graph_traversal() {
+        let store = store();
+        let graph = SimpleGraphDefinition::build_graph(&store);
+        let arena = SimpleGraphDefinition::build_arena(&store);
+
+        let traverse = |meta: crate::graph::traversal::TraversalMeta<Id>| {
+            if meta.depth() > 1 {
+                Instruction::Backtrack(())
+            } else {
+                Instruction::Continue(())
+            }
+        };
+
+        let graph_log = traverse_graph(graph.query(&1).unwrap().clone())
+            .set_mode(Mode::Acyclic)
+            .execute(|meta, _| traverse(meta));
+        let arena_log = traverse_graph(arena.node(&1).unwrap())
+            .set_mode(Mode::Acyclic)
+            .execute(|meta, edge| {
+                edge.target.annotate(edge.origin.get_id());
+                traverse(meta)
+            });
+
+        assert_eq!(graph_log.nodes, arena_log.nodes);
+        assert_eq!(graph_log.edges, arena_log.edges);
+
+        let projected_graph = graph_log.project_into_graph(&graph);
+        let projected_arena = arena_log.project_into_arena(&arena);
+        assert_eq!(projected_arena.order(), projected_graph.order());
+        assert_eq!(projected_arena.size(), projected_graph.all_edges().count());
+        assert!(projected_arena.node(&5).is_none());
+
+        // Projections share annotations with the graph they came from
+        let four = projected_arena.node(&4).unwrap();
+        assert_eq!(four.get_annotation::<Id>(), Some(3));
+        assert_eq!(targets(four.edges()), Vec::<Id>::new());
+    }
+
+    #[test]
+    fn arena_traversal_can_be_extracted() {
+        let store = store();
+        let graph = SimpleGraphDefinition::build_graph(&store);
+        let arena = SimpleGraphDefinition::build_arena(&store);
+        arena.node(&2).unwrap().annotate("annotation");
+
+        let log = traverse_graph(arena.node(&2).unwrap())
+            .set_mode(Mode::Acyclic)
+            .execute(|_, _| Instruction::Continue(()));
+        let extracted = log.extract_from_arena(&arena);
+        let projected = log.project_into_graph(&graph);
+
+        assert_eq!(extracted.order(), projected.order());
+        assert_eq!(extracted.all_edges().count(), projected.all_edges().count());
+        let two = extracted.query(&2).unwrap();
+        assert_eq!(two.get_edge(&4).unwrap().target.get_id(), 4);
+        assert!(extracted.query(&5).is_none());
+        assert_eq!(two.get_annotation::<&str>(), None);
+    }
+}
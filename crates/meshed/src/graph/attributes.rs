@@ -0,0 +1,105 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Labels and data carried by nodes and edges when exporting graphs, shared
+//! by [`graphviz`](super::graphviz), [`mermaid`](super::mermaid) and
+//! [`graphml`](super::graphml).
+
+use std::fmt::{Display, Formatter};
+
+use crate::graph::edge::Edge;
+use crate::graph::node::Node;
+use crate::graph::{GraphDefinition, SimpleGraphDefinition};
+
+/// A typed value attached to a node or an edge
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    Text(String),
+    Number(f64),
+    Boolean(bool),
+}
+
+impl Display for AttributeValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttributeValue::Text(value) => write!(f, "{}", value),
+            AttributeValue::Number(value) => write!(f, "{}", value),
+            AttributeValue::Boolean(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        AttributeValue::Text(value)
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        AttributeValue::Text(value.to_string())
+    }
+}
+
+impl From<f32> for AttributeValue {
+    fn from(value: f32) -> Self {
+        AttributeValue::Number(value as f64)
+    }
+}
+
+impl From<f64> for AttributeValue {
+    fn from(value: f64) -> Self {
+        AttributeValue::Number(value)
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(value: bool) -> Self {
+        AttributeValue::Boolean(value)
+    }
+}
+
+pub type Attributes = Vec<(&'static str, AttributeValue)>;
+
+/// Pick what is exported for each node and edge. Every method has a
+/// default, so an empty impl exports nodes by their label and nothing else
+pub trait GraphAttributes: GraphDefinition + Sized {
+    fn node_label(node: &Node<Self>) -> String {
+        node.label().to_string()
+    }
+
+    /// Node data, e.g. `("size", 1024.0.into())`
+    fn node_attributes(_node: &Node<Self>) -> Attributes {
+        vec![]
+    }
+
+    fn edge_label(_edge: &Edge<Self>) -> Option<String> {
+        None
+    }
+
+    /// Edge metadata
+    fn edge_attributes(_edge: &Edge<Self>) -> Attributes {
+        vec![]
+    }
+
+    /// Formats that can draw dashed edges use this to tell them apart,
+    /// e.g. for asynchronous dependencies
+    fn edge_dashed(_edge: &Edge<Self>) -> bool {
+        false
+    }
+}
+
+impl GraphAttributes for SimpleGraphDefinition {}
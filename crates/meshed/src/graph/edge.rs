@@ -17,20 +17,20 @@
 use crate::graph::node::Node;
 use crate::graph::GraphDefinition;
 use crate::identify::{Identifiable, Identity};
+use crate::rc::RefCount;
 use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
 use std::marker::PhantomData;
-use std::rc::Rc;
 
 pub struct Edge<T: GraphDefinition> {
     pub origin: Node<T>,
     pub target: Node<T>,
-    pub meta: Rc<T::EdgeMeta>,
+    pub meta: RefCount<T::EdgeMeta>,
     pub graph_type: PhantomData<T>,
 }
 
 impl<T: GraphDefinition> Edge<T> {
-    pub fn new(from: Node<T>, to: Node<T>, meta: Rc<T::EdgeMeta>) -> Self {
+    pub fn new(from: Node<T>, to: Node<T>, meta: RefCount<T::EdgeMeta>) -> Self {
         Self {
             origin: from,
             target: to,
@@ -45,7 +45,7 @@ impl<T: GraphDefinition> Clone for Edge<T> {
         Self {
             origin: self.origin.clone(),
             target: self.target.clone(),
-            meta: Rc::clone(&self.meta),
+            meta: RefCount::clone(&self.meta),
             graph_type: Default::default(),
         }
     }
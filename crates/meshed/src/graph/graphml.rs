@@ -0,0 +1,251 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Render graphs as [GraphML](http://graphml.graphdrawing.org), which yEd,
+//! Gephi and most graph tooling can read.
+//!
+//! Labels are written under the `label` key. Every attribute from
+//! [`GraphAttributes`] gets its own key, typed from the values it holds.
+//!
+//! ```
+//! # use meshed::graph::{Graph, SimpleGraphDefinition};
+//! # use meshed::prelude::*;
+//! use meshed::graph::graphml::render;
+//!
+//! let mut graph: Graph<SimpleGraphDefinition> = Default::default();
+//! graph.insert_node(0);
+//! graph.insert_node(1);
+//! graph.insert_edge(0, 1);
+//!
+//! let mut output = vec![];
+//! render(&graph, &mut output).unwrap();
+//! let output = String::from_utf8(output).unwrap();
+//! assert!(output.contains(r#"<edge source="0" target="1">"#));
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::graph::attributes::{AttributeValue, Attributes, GraphAttributes};
+use crate::graph::ConcreteGraph;
+use crate::prelude::*;
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+fn attribute_type(value: &AttributeValue) -> &'static str {
+    match value {
+        AttributeValue::Text(_) => "string",
+        AttributeValue::Number(_) => "double",
+        AttributeValue::Boolean(_) => "boolean",
+    }
+}
+
+/// Keys of either nodes or edges, with the GraphML type of their values.
+/// Keys holding values of different types fall back to strings
+#[derive(Default)]
+struct Keys(BTreeMap<&'static str, &'static str>);
+
+impl Keys {
+    fn add(&mut self, attributes: &Attributes) {
+        for (key, value) in attributes {
+            let value_type = attribute_type(value);
+            let existing = self.0.entry(key).or_insert(value_type);
+            if *existing != value_type {
+                *existing = "string";
+            }
+        }
+    }
+
+    fn write<W: Write>(&self, writer: &mut W, domain: &str) -> io::Result<()> {
+        writeln!(
+            writer,
+            r#"  <key id="{0}_label" for="{0}" attr.name="label" attr.type="string"/>"#,
+            domain
+        )?;
+        for (key, value_type) in self.0.iter() {
+            writeln!(
+                writer,
+                r#"  <key id="{0}_{1}" for="{0}" attr.name="{1}" attr.type="{2}"/>"#,
+                domain,
+                escape(key),
+                value_type
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn write_data<W: Write>(
+    writer: &mut W,
+    domain: &str,
+    label: Option<String>,
+    attributes: &Attributes,
+) -> io::Result<()> {
+    if let Some(label) = label {
+        writeln!(
+            writer,
+            r#"      <data key="{}_label">{}</data>"#,
+            domain,
+            escape(&label)
+        )?;
+    }
+    for (key, value) in attributes {
+        writeln!(
+            writer,
+            r#"      <data key="{}_{}">{}</data>"#,
+            domain,
+            escape(key),
+            escape(&value.to_string())
+        )?;
+    }
+    Ok(())
+}
+
+/// Render every node and edge of `graph` as a directed GraphML document
+pub fn render<T, W>(graph: &ConcreteGraph<T>, writer: &mut W) -> io::Result<()>
+where
+    T: GraphAttributes,
+    W: Write,
+{
+    let mut nodes: Vec<_> = graph.all_nodes().collect();
+    nodes.sort_by_key(|node| node.get_id());
+    let nodes: Vec<_> = nodes
+        .into_iter()
+        .map(|node| {
+            let label = T::node_label(&node);
+            let attributes = T::node_attributes(&node);
+            (node, label, attributes)
+        })
+        .collect();
+
+    let mut edges: Vec<_> = graph.all_edges().collect();
+    edges.sort_by_key(|edge| (edge.origin.get_id(), edge.target.get_id()));
+    let edges: Vec<_> = edges
+        .into_iter()
+        .map(|edge| {
+            let label = T::edge_label(&edge);
+            let attributes = T::edge_attributes(&edge);
+            (edge, label, attributes)
+        })
+        .collect();
+
+    let mut node_keys = Keys::default();
+    for (_, _, attributes) in nodes.iter() {
+        node_keys.add(attributes);
+    }
+    let mut edge_keys = Keys::default();
+    for (_, _, attributes) in edges.iter() {
+        edge_keys.add(attributes);
+    }
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    node_keys.write(writer, "node")?;
+    edge_keys.write(writer, "edge")?;
+    writeln!(writer, r#"  <graph id="G" edgedefault="directed">"#)?;
+
+    for (node, label, attributes) in nodes {
+        writeln!(
+            writer,
+            r#"    <node id="{}">"#,
+            escape(&node.get_id().to_string())
+        )?;
+        write_data(writer, "node", Some(label), &attributes)?;
+        writeln!(writer, "    </node>")?;
+    }
+
+    for (edge, label, attributes) in edges {
+        writeln!(
+            writer,
+            r#"    <edge source="{}" target="{}">"#,
+            escape(&edge.origin.get_id().to_string()),
+            escape(&edge.target.get_id().to_string())
+        )?;
+        write_data(writer, "edge", label, &attributes)?;
+        writeln!(writer, "    </edge>")?;
+    }
+
+    writeln!(writer, "  </graph>")?;
+    writeln!(writer, "</graphml>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::edge::Edge;
+    use crate::graph::node::Node;
+    use crate::graph::GraphDefinition;
+    use crate::rc::RefCount;
+
+    struct Weighted;
+    impl GraphDefinition for Weighted {
+        type Id = i32;
+        type Label = String;
+        type EdgeMeta = &'static str;
+        type NodeData = f32;
+    }
+
+    impl GraphAttributes for Weighted {
+        fn node_attributes(node: &Node<Self>) -> Attributes {
+            vec![("size", (*node.node_data()).into())]
+        }
+
+        fn edge_attributes(edge: &Edge<Self>) -> Attributes {
+            vec![("kind", (*edge.meta).into())]
+        }
+    }
+
+    #[test]
+    fn writes_typed_keys_and_escaped_data() {
+        let mut graph: ConcreteGraph<Weighted> = ConcreteGraph {
+            nodes: Default::default(),
+        };
+        let origin = Node::<Weighted>::new(0, "<entry>".to_string(), 10.0);
+        let target = Node::<Weighted>::new(1, "a & b".to_string(), 2.5);
+        origin.insert_edge(target.clone(), RefCount::new("import"));
+        graph.nodes.insert(0, origin);
+        graph.nodes.insert(1, target);
+
+        let mut output = vec![];
+        render(&graph, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output
+            .contains(r#"<key id="node_size" for="node" attr.name="size" attr.type="double"/>"#));
+        assert!(output
+            .contains(r#"<key id="edge_kind" for="edge" attr.name="kind" attr.type="string"/>"#));
+        assert!(output.contains(r#"<data key="node_label">&lt;entry&gt;</data>"#));
+        assert!(output.contains(r#"<data key="node_label">a &amp; b</data>"#));
+        assert!(output.contains(r#"<data key="node_size">2.5</data>"#));
+        assert!(output.contains(r#"<data key="edge_kind">import</data>"#));
+    }
+}
@@ -0,0 +1,271 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Render graphs in the graphviz DOT format.
+//!
+//! Labels, dashed edges and attributes come from [`GraphAttributes`], like
+//! every other export format. Attributes are shown as tooltips. [`DotStyle`]
+//! adds what only DOT can draw: node sizes and clusters. Every method has a
+//! default, so empty impls render nodes by their label.
+//!
+//! ```
+//! # use meshed::graph::{Graph, SimpleGraphDefinition};
+//! # use meshed::prelude::*;
+//! use meshed::graph::graphviz::render;
+//!
+//! let mut graph: Graph<SimpleGraphDefinition> = Default::default();
+//! graph.insert_node(0);
+//! graph.insert_node(1);
+//! graph.insert_edge(0, 1);
+//!
+//! let mut output = vec![];
+//! render(&graph, &mut output).unwrap();
+//! let output = String::from_utf8(output).unwrap();
+//! assert!(output.contains(r#""0" -> "1""#));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+
+use crate::graph::attributes::{Attributes, GraphAttributes};
+use crate::graph::edge::Edge;
+use crate::graph::node::Node;
+use crate::graph::{ConcreteGraph, GraphDefinition, SimpleGraphDefinition};
+use crate::prelude::*;
+
+/// Smallest and largest side of a node, in inches, when nodes are sized
+const MIN_NODE_SIDE: f32 = 0.5;
+const MAX_NODE_SIDE: f32 = 3.0;
+
+pub trait DotStyle: GraphAttributes {
+    /// What the node weighs. Node areas are scaled relative to the heaviest
+    /// node. Nodes are not sized when this is `None`
+    fn node_size(_node: &Node<Self>) -> Option<f32> {
+        None
+    }
+
+    /// Nodes of the same cluster are drawn inside a `subgraph cluster_<name>`
+    fn node_cluster(_node: &Node<Self>) -> Option<String> {
+        None
+    }
+}
+
+impl DotStyle for SimpleGraphDefinition {}
+
+/// Origin and target of an edge
+type NodePair<T> = (<T as GraphDefinition>::Id, <T as GraphDefinition>::Id);
+
+/// Quote and escape a string for use as a DOT id or attribute value
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            character => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// One `key: value` line per attribute
+fn tooltip(attributes: &Attributes) -> String {
+    attributes
+        .iter()
+        .map(|(key, value)| format!("{}: {}", key, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn write_attributes<W: Write>(
+    writer: &mut W,
+    attributes: &[(&'static str, String)],
+) -> io::Result<()> {
+    if attributes.is_empty() {
+        return Ok(());
+    }
+
+    let attributes = attributes
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, quote(value)))
+        .collect::<Vec<_>>();
+    write!(writer, " [{}]", attributes.join(", "))
+}
+
+/// Render every node and edge of `graph` as a directed DOT graph
+pub fn render<T, W>(graph: &ConcreteGraph<T>, writer: &mut W) -> io::Result<()>
+where
+    T: DotStyle,
+    W: Write,
+{
+    let mut nodes: Vec<_> = graph.all_nodes().collect();
+    nodes.sort_by_key(|node| node.get_id());
+
+    let heaviest = nodes
+        .iter()
+        .filter_map(T::node_size)
+        .fold(0.0_f32, f32::max);
+
+    let mut clusters: BTreeMap<String, Vec<&Node<T>>> = BTreeMap::new();
+    let mut unclustered = vec![];
+    for node in nodes.iter() {
+        match T::node_cluster(node) {
+            Some(cluster) => clusters.entry(cluster).or_default().push(node),
+            None => unclustered.push(node),
+        }
+    }
+
+    let write_node = |writer: &mut W, node: &Node<T>, indent: &str| -> io::Result<()> {
+        let mut attributes = vec![("label", T::node_label(node))];
+        if let Some(size) = T::node_size(node).filter(|_| heaviest > 0.0) {
+            let side =
+                MIN_NODE_SIDE + (MAX_NODE_SIDE - MIN_NODE_SIDE) * (size / heaviest).max(0.0).sqrt();
+            attributes.push(("width", format!("{:.2}", side)));
+            attributes.push(("height", format!("{:.2}", side)));
+        }
+        let data = tooltip(&T::node_attributes(node));
+        if !data.is_empty() {
+            attributes.push(("tooltip", data));
+        }
+
+        write!(writer, "{}{}", indent, quote(&node.get_id().to_string()))?;
+        write_attributes(writer, &attributes)?;
+        writeln!(writer, ";")
+    };
+
+    writeln!(writer, "digraph {{")?;
+    writeln!(writer, "    node [shape=box];")?;
+    for (cluster, nodes) in clusters.iter() {
+        writeln!(
+            writer,
+            "    subgraph {} {{",
+            quote(&format!("cluster_{}", cluster))
+        )?;
+        writeln!(writer, "        label={};", quote(cluster))?;
+        for node in nodes {
+            write_node(writer, node, "        ")?;
+        }
+        writeln!(writer, "    }}")?;
+    }
+    for node in unclustered {
+        write_node(writer, node, "    ")?;
+    }
+
+    // Nodes can be connected by several edges. Draw a single one, dashed
+    // when every edge is dashed
+    let mut edges: BTreeMap<NodePair<T>, Vec<Edge<T>>> = BTreeMap::new();
+    for edge in graph.all_edges() {
+        edges
+            .entry((edge.origin.get_id(), edge.target.get_id()))
+            .or_default()
+            .push(edge);
+    }
+    for ((origin, target), edges) in edges {
+        let mut attributes = vec![];
+        if let Some(label) = edges.iter().find_map(T::edge_label) {
+            attributes.push(("label", label));
+        }
+        if edges.iter().all(T::edge_dashed) {
+            attributes.push(("style", "dashed".to_string()));
+        }
+        let data = edges
+            .iter()
+            .map(|edge| tooltip(&T::edge_attributes(edge)))
+            .filter(|data| !data.is_empty())
+            .collect::<BTreeSet<_>>();
+        if !data.is_empty() {
+            attributes.push(("tooltip", data.into_iter().collect::<Vec<_>>().join("\n")));
+        }
+
+        write!(
+            writer,
+            "    {} -> {}",
+            quote(&origin.to_string()),
+            quote(&target.to_string())
+        )?;
+        write_attributes(writer, &attributes)?;
+        writeln!(writer, ";")?;
+    }
+
+    writeln!(writer, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rc::RefCount;
+
+    struct Weighted;
+    impl GraphDefinition for Weighted {
+        type Id = i32;
+        type Label = String;
+        type EdgeMeta = bool;
+        type NodeData = f32;
+    }
+
+    impl GraphAttributes for Weighted {
+        fn node_attributes(node: &Node<Self>) -> Attributes {
+            vec![("weight", (*node.node_data()).into())]
+        }
+
+        fn edge_dashed(edge: &Edge<Self>) -> bool {
+            *edge.meta
+        }
+    }
+
+    impl DotStyle for Weighted {
+        fn node_size(node: &Node<Self>) -> Option<f32> {
+            Some(*node.node_data())
+        }
+
+        fn node_cluster(node: &Node<Self>) -> Option<String> {
+            (node.get_id() % 2 == 0).then(|| "even".to_string())
+        }
+    }
+
+    #[test]
+    fn renders_clusters_sizes_and_dashed_edges() {
+        let mut graph: ConcreteGraph<Weighted> = ConcreteGraph {
+            nodes: Default::default(),
+        };
+        let nodes: Vec<_> = [(0, 100.0), (1, 25.0), (2, 0.0)]
+            .into_iter()
+            .map(|(id, size)| Node::<Weighted>::new(id, format!("node \"{}\"", id), size))
+            .collect();
+        nodes[0].insert_edge(nodes[1].clone(), RefCount::new(true));
+        nodes[0].insert_edge(nodes[2].clone(), RefCount::new(false));
+        for node in nodes {
+            graph.nodes.insert(node.get_id(), node);
+        }
+
+        let mut output = vec![];
+        render(&graph, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains(r#"subgraph "cluster_even" {"#));
+        assert!(output.contains(
+            r#""0" [label="node \"0\"", width="3.00", height="3.00", tooltip="weight: 100"];"#
+        ));
+        assert!(output.contains(
+            r#""1" [label="node \"1\"", width="1.75", height="1.75", tooltip="weight: 25"];"#
+        ));
+        assert!(output.contains(r#""0" -> "1" [style="dashed"];"#));
+        assert!(output.contains("\"0\" -> \"2\";"));
+    }
+}
@@ -0,0 +1,181 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Render graphs as [Mermaid](https://mermaid.js.org) flowcharts.
+//!
+//! Nodes show their label followed by one `key: value` line per attribute.
+//! Edges show their label and attributes. See [`GraphAttributes`].
+//!
+//! ```
+//! # use meshed::graph::{Graph, SimpleGraphDefinition};
+//! # use meshed::prelude::*;
+//! use meshed::graph::mermaid::render;
+//!
+//! let mut graph: Graph<SimpleGraphDefinition> = Default::default();
+//! graph.insert_node(0);
+//! graph.insert_node(1);
+//! graph.insert_edge(0, 1);
+//!
+//! let mut output = vec![];
+//! render(&graph, &mut output).unwrap();
+//! let output = String::from_utf8(output).unwrap();
+//! assert!(output.contains("n0 --> n1"));
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::graph::attributes::{AttributeValue, GraphAttributes};
+use crate::graph::ConcreteGraph;
+use crate::prelude::*;
+
+/// Mermaid text goes inside double quotes. Characters that would end the
+/// string, start an entity code or be read as html are written as entity
+/// codes
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '#' => escaped.push_str("#35;"),
+            '"' => escaped.push_str("#quot;"),
+            '<' => escaped.push_str("#lt;"),
+            '>' => escaped.push_str("#gt;"),
+            '\n' => escaped.push_str("<br/>"),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+fn describe(label: Option<String>, attributes: &[(&'static str, AttributeValue)]) -> Vec<String> {
+    label
+        .into_iter()
+        .chain(
+            attributes
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, value)),
+        )
+        .map(|line| escape(&line))
+        .collect()
+}
+
+/// Render every node and edge of `graph` as a top to bottom flowchart
+pub fn render<T, W>(graph: &ConcreteGraph<T>, writer: &mut W) -> io::Result<()>
+where
+    T: GraphAttributes,
+    W: Write,
+{
+    let mut nodes: Vec<_> = graph.all_nodes().collect();
+    nodes.sort_by_key(|node| node.get_id());
+
+    // Node ids can contain anything. Refer to nodes by their position instead
+    let mut names = BTreeMap::new();
+    writeln!(writer, "flowchart TB")?;
+    for (index, node) in nodes.iter().enumerate() {
+        let name = format!("n{}", index);
+        let text = describe(Some(T::node_label(node)), &T::node_attributes(node));
+        writeln!(writer, "    {}[\"{}\"]", name, text.join("<br/>"))?;
+        names.insert(node.get_id(), name);
+    }
+
+    let mut edges: Vec<_> = graph.all_edges().collect();
+    edges.sort_by_key(|edge| (edge.origin.get_id(), edge.target.get_id()));
+    for edge in edges {
+        let (origin, target) = match (
+            names.get(&edge.origin.get_id()),
+            names.get(&edge.target.get_id()),
+        ) {
+            (Some(origin), Some(target)) => (origin, target),
+            _ => continue,
+        };
+        let arrow = if T::edge_dashed(&edge) { "-.->" } else { "-->" };
+        let text = describe(T::edge_label(&edge), &T::edge_attributes(&edge));
+        if text.is_empty() {
+            writeln!(writer, "    {} {} {}", origin, arrow, target)?;
+        } else {
+            writeln!(
+                writer,
+                "    {} {}|\"{}\"| {}",
+                origin,
+                arrow,
+                text.join("<br/>"),
+                target
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::edge::Edge;
+    use crate::graph::node::Node;
+    use crate::graph::GraphDefinition;
+    use crate::rc::RefCount;
+
+    #[test]
+    fn escapes_quotes_entities_and_html() {
+        assert_eq!(
+            escape("say \"<b>hi</b>\"\n#quot;"),
+            "say #quot;#lt;b#gt;hi#lt;/b#gt;#quot;<br/>#35;quot;"
+        );
+        assert_eq!(
+            escape("./src/index.js + 2 modules"),
+            "./src/index.js + 2 modules"
+        );
+    }
+
+    #[test]
+    fn renders_escaped_labels() {
+        struct Quoted;
+        impl GraphDefinition for Quoted {
+            type Id = i32;
+            type Label = String;
+            type EdgeMeta = ();
+            type NodeData = ();
+        }
+        impl GraphAttributes for Quoted {
+            fn node_label(node: &Node<Self>) -> String {
+                format!("\"{}\" <{}>", node.get_id(), node.get_id())
+            }
+
+            fn edge_label(_edge: &Edge<Self>) -> Option<String> {
+                Some("import \"a\"".to_string())
+            }
+        }
+
+        let mut graph: ConcreteGraph<Quoted> = ConcreteGraph {
+            nodes: Default::default(),
+        };
+        let nodes: Vec<_> = [0, 1]
+            .into_iter()
+            .map(|id| Node::<Quoted>::new(id, String::new(), ()))
+            .collect();
+        nodes[0].insert_edge(nodes[1].clone(), RefCount::new(()));
+        for node in nodes {
+            graph.nodes.insert(node.get_id(), node);
+        }
+
+        let mut output = vec![];
+        render(&graph, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains(r##"n0["#quot;0#quot; #lt;0#gt;"]"##));
+        assert!(output.contains(r##"n0 -->|"import #quot;a#quot;"| n1"##));
+    }
+}
@@ -14,7 +14,6 @@
  * limitations under the License.
  */
 
-use std::cell::Cell;
 use std::cmp::Ordering;
 use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
@@ -23,18 +22,18 @@ use crate::anymap::AnymapCell;
 use crate::graph::edge::Edge;
 use crate::graph::{GraphDefinition, SimpleGraphDefinition};
 use crate::identify::Identifiable;
-use std::rc::Rc;
+use crate::rc::{RefCount, Shareable, SharedCell};
 
 pub struct Node<T: GraphDefinition> {
     id: T::Id,
-    label: Rc<T::Label>,
-    data: Rc<T::NodeData>,
-    annotations: Rc<AnymapCell>,
-    edges: Rc<Edges<T>>,
+    label: RefCount<T::Label>,
+    data: RefCount<T::NodeData>,
+    annotations: RefCount<AnymapCell>,
+    edges: RefCount<Edges<T>>,
     graph_type: PhantomData<T>,
 }
 
-type Edges<T> = Cell<Vec<Edge<T>>>;
+type Edges<T> = SharedCell<Vec<Edge<T>>>;
 
 impl Node<SimpleGraphDefinition> {
     pub fn new_bare(identity: <SimpleGraphDefinition as GraphDefinition>::Id) -> Self {
@@ -51,8 +50,24 @@ impl<T: GraphDefinition> Node<T> {
         Self {
             id: identity,
             annotations: Default::default(),
-            label: Rc::new(label),
-            data: Rc::new(data),
+            label: RefCount::new(label),
+            data: RefCount::new(data),
+            edges: Default::default(),
+            graph_type: Default::default(),
+        }
+    }
+
+    /// A node that shares its label and data with the node of another graph
+    pub(crate) fn new_shared(
+        identity: T::Id,
+        label: RefCount<T::Label>,
+        data: RefCount<T::NodeData>,
+    ) -> Self {
+        Self {
+            id: identity,
+            annotations: Default::default(),
+            label,
+            data,
             edges: Default::default(),
             graph_type: Default::default(),
         }
@@ -62,11 +77,11 @@ impl<T: GraphDefinition> Node<T> {
         self.label.as_ref()
     }
 
-    pub fn annotate<A: 'static>(&self, value: A) -> Option<A> {
+    pub fn annotate<A: Shareable>(&self, value: A) -> Option<A> {
         self.annotations.insert(value)
     }
 
-    pub fn get_annotation<A: Clone + 'static>(&self) -> Option<A> {
+    pub fn get_annotation<A: Clone + Shareable>(&self) -> Option<A> {
         self.annotations.get()
     }
 
@@ -76,7 +91,7 @@ impl<T: GraphDefinition> Node<T> {
 
     pub fn new_derived(&self) -> Self {
         Self {
-            data: Rc::clone(&self.data),
+            data: RefCount::clone(&self.data),
             edges: Default::default(),
             label: self.label.clone(),
             annotations: Default::default(), // We don't preserve annotations
@@ -87,10 +102,10 @@ impl<T: GraphDefinition> Node<T> {
 
     pub fn new_derived_with_annotations(&self) -> Self {
         Self {
-            data: Rc::clone(&self.data),
+            data: RefCount::clone(&self.data),
             edges: Default::default(),
             label: self.label.clone(),
-            annotations: Rc::clone(&self.annotations), // We don't preserve annotations
+            annotations: RefCount::clone(&self.annotations), // We don't preserve annotations
             id: self.id.clone(),
             graph_type: Default::default(),
         }
@@ -102,39 +117,28 @@ where
     T: GraphDefinition,
 {
     pub fn derive_edge(&self, target: Self, source: &Edge<T>) {
-        let mut edges = self.edges.take();
-        edges.push(Edge::new(self.clone(), target, source.meta.clone()));
-        self.edges.set(edges);
+        let edge = Edge::new(self.clone(), target, source.meta.clone());
+        self.edges.with_mut(|edges| edges.push(edge));
     }
-    pub fn insert_edge(&self, target: Self, meta: Rc<T::EdgeMeta>) {
-        let mut edges = self.edges.take();
-        edges.push(Edge::new(self.clone(), target, meta));
-        self.edges.set(edges);
+    pub fn insert_edge(&self, target: Self, meta: RefCount<T::EdgeMeta>) {
+        let edge = Edge::new(self.clone(), target, meta);
+        self.edges.with_mut(|edges| edges.push(edge));
     }
 
     pub fn get_edge_ref(&self, index: usize) -> Option<Edge<T>> {
-        let edges = self.edges.take();
-        let edge = edges.iter().nth(index).cloned();
-        self.edges.set(edges);
-        edge
+        self.edges.with(|edges| edges.get(index).cloned())
     }
 
     pub fn get_edge(&self, id: &T::Id) -> Option<Edge<T>> {
-        let edges = self.edges.take();
-        let edge = edges.iter().find(|edge| &edge.target.id == id).cloned();
-        self.edges.set(edges);
-        edge
+        self.edges
+            .with(|edges| edges.iter().find(|edge| &edge.target.id == id).cloned())
     }
 
     pub fn with_edges_iter<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&mut dyn Iterator<Item = &Edge<T>>) -> R,
     {
-        let edges = self.edges.take();
-        let mut iter = edges.iter();
-        let ret = f(&mut iter);
-        self.edges.set(edges);
-        ret
+        self.edges.with(|edges| f(&mut edges.iter()))
     }
 
     pub fn find_edge<F>(&self, mut find_fn: F) -> Option<Edge<T>>
@@ -167,10 +171,10 @@ where
     fn clone(&self) -> Self {
         Self {
             id: self.id.clone(),
-            data: Rc::clone(&self.data),
-            label: Rc::clone(&self.label),
-            annotations: Rc::clone(&self.annotations),
-            edges: Rc::clone(&self.edges),
+            data: RefCount::clone(&self.data),
+            label: RefCount::clone(&self.label),
+            annotations: RefCount::clone(&self.annotations),
+            edges: RefCount::clone(&self.edges),
             graph_type: Default::default(),
         }
     }
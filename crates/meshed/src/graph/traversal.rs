@@ -16,17 +16,14 @@
 
 use crate::graph::edge::Edge;
 
-use std::cell::Cell;
-
 use crate::graph::{Graph, GraphDefinition};
 
 use crate::anymap::AnymapCell;
 use std::collections::{HashSet, VecDeque};
 
-use std::rc::Rc;
-
 use crate::graph::node::Node;
 use crate::identify::{Identifiable, Identity};
+use crate::rc::{RefCount, Shareable, SharedCell};
 
 pub type GraphTraversal<T: GraphDefinition> = TraversalLog<T::Id>;
 
@@ -244,12 +241,56 @@ where
 ///# assert_eq!(log.nodes, HashSet::from([0, 1, 2, 3]));
 ///# assert_eq!(log.edges, HashSet::from([(0, 1), (0, 2), (1, 2), (1, 3),  (2, 3)]))
 ///```
-pub struct TraverseGraph<T: GraphDefinition, Mode> {
-    traversal_log: TraversalLog<T::Id>,
-    queue: VecDeque<(TraversalMeta<T::Id>, Edge<T>)>,
+pub struct TraverseGraph<N: TraversableNode, Mode> {
+    traversal_log: TraversalLog<N::Id>,
+    queue: VecDeque<(TraversalMeta<N::Id>, N::Edge)>,
     mode: Mode,
 }
 
+/// A node that can be walked by [`traverse_graph`]. Implemented by [`Node`] and
+/// by the nodes of an [`ArenaGraph`](crate::graph::arena::ArenaGraph).
+pub trait TraversableNode: Clone {
+    type Id: Identity;
+    type Edge: TraversableEdge<Node = Self>;
+
+    fn node_id(&self) -> Self::Id;
+
+    /// Visit every outgoing edge of the node in order
+    fn for_each_edge<F: FnMut(Self::Edge)>(&self, visit: F);
+}
+
+pub trait TraversableEdge: Clone {
+    type Node: TraversableNode;
+
+    fn origin_node(&self) -> &Self::Node;
+    fn target_node(&self) -> &Self::Node;
+}
+
+impl<T: GraphDefinition> TraversableNode for Node<T> {
+    type Id = T::Id;
+    type Edge = Edge<T>;
+
+    fn node_id(&self) -> Self::Id {
+        self.get_id()
+    }
+
+    fn for_each_edge<F: FnMut(Self::Edge)>(&self, visit: F) {
+        self.with_edges_iter(|edges| edges.cloned().for_each(visit))
+    }
+}
+
+impl<T: GraphDefinition> TraversableEdge for Edge<T> {
+    type Node = Node<T>;
+
+    fn origin_node(&self) -> &Self::Node {
+        &self.origin
+    }
+
+    fn target_node(&self) -> &Self::Node {
+        &self.target
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct New(Mode, Pathing);
 
@@ -282,15 +323,13 @@ pub enum Mode {
     Simple,
 }
 
-pub fn traverse_graph<T: GraphDefinition>(start_node: Node<T>) -> TraverseGraph<T, New> {
+pub fn traverse_graph<N: TraversableNode>(start_node: N) -> TraverseGraph<N, New> {
     let mut queue = VecDeque::new();
 
-    start_node.with_edges_iter(|edge| {
-        queue.extend(edge.cloned().map(|edge| (TraversalMeta::new(1), edge)))
-    });
+    start_node.for_each_edge(|edge| queue.push_back((TraversalMeta::new(1), edge)));
 
     let mut nodes = HashSet::new();
-    nodes.insert(start_node.get_id());
+    nodes.insert(start_node.node_id());
     TraverseGraph {
         traversal_log: TraversalLog {
             edges: Default::default(),
@@ -301,9 +340,9 @@ pub fn traverse_graph<T: GraphDefinition>(start_node: Node<T>) -> TraverseGraph<
     }
 }
 
-impl<T> TraverseGraph<T, New>
+impl<N> TraverseGraph<N, New>
 where
-    T: GraphDefinition,
+    N: TraversableNode,
 {
     pub fn set_pathing(mut self, pathing: Pathing) -> Self {
         self.mode.1 = pathing;
@@ -315,9 +354,9 @@ where
         self
     }
 
-    pub fn execute<F>(self, mut test_fn: F) -> TraversalLog<T::Id>
+    pub fn execute<F>(self, mut test_fn: F) -> TraversalLog<N::Id>
     where
-        F: FnMut(TraversalMeta<T::Id>, Edge<T>) -> Instruction<()>,
+        F: FnMut(TraversalMeta<N::Id>, N::Edge) -> Instruction<()>,
     {
         let mut started = self.start();
         loop {
@@ -332,7 +371,7 @@ where
         started.traversal_log
     }
 
-    pub fn start(self) -> TraverseGraph<T, Started> {
+    pub fn start(self) -> TraverseGraph<N, Started> {
         TraverseGraph {
             traversal_log: self.traversal_log,
             queue: self.queue,
@@ -340,9 +379,9 @@ where
         }
     }
 
-    pub fn into_iterator<F, R>(self, test_fn: F) -> GraphIterator<T, F, R>
+    pub fn into_iterator<F, R>(self, test_fn: F) -> GraphIterator<N, F, R>
     where
-        F: FnMut(TraversalMeta<T::Id>, Edge<T>) -> Instruction<R>,
+        F: FnMut(TraversalMeta<N::Id>, N::Edge) -> Instruction<R>,
     {
         GraphIterator {
             iterator: self.start(),
@@ -351,29 +390,29 @@ where
     }
 }
 
-impl<T> TraverseGraph<T, Started>
+impl<N> TraverseGraph<N, Started>
 where
-    T: GraphDefinition,
+    N: TraversableNode,
 {
     pub fn drive_edge<F, R>(&mut self, mut test_fn: F) -> Option<R>
     where
-        F: FnMut(TraversalMeta<T::Id>, Edge<T>) -> Instruction<R>,
+        F: FnMut(TraversalMeta<N::Id>, N::Edge) -> Instruction<R>,
     {
         let (meta, edge) = match self.mode.1 {
             Pathing::BFS => self.queue.pop_front()?,
             Pathing::DFS => self.queue.pop_back()?,
         };
+        let origin = edge.origin_node().node_id();
+        let target = edge.target_node().node_id();
 
         match (self.mode, test_fn(meta.clone(), edge.clone())) {
             // Backtrack if acyclic node has been previously traversed
             (Started(Mode::Acyclic, _), Instruction::Continue(result))
-                if self.traversal_log.nodes.contains(&edge.target.get_id()) =>
+                if self.traversal_log.nodes.contains(&target) =>
             {
-                self.traversal_log.nodes.insert(edge.target.get_id());
-                self.traversal_log.nodes.insert(edge.origin.get_id());
-                self.traversal_log
-                    .edges
-                    .insert((edge.origin.get_id(), edge.target.get_id()));
+                self.traversal_log.nodes.insert(target.clone());
+                self.traversal_log.nodes.insert(origin.clone());
+                self.traversal_log.edges.insert((origin, target));
 
                 Some(result)
             }
@@ -383,28 +422,21 @@ where
             }
             (_, Instruction::Skip(result)) => Some(result),
             (_, Instruction::Backtrack(result)) => {
-                self.traversal_log.nodes.insert(edge.target.get_id());
-                self.traversal_log.nodes.insert(edge.origin.get_id());
-                self.traversal_log
-                    .edges
-                    .insert((edge.origin.get_id(), edge.target.get_id()));
+                self.traversal_log.nodes.insert(target.clone());
+                self.traversal_log.nodes.insert(origin.clone());
+                self.traversal_log.edges.insert((origin, target));
 
                 Some(result)
             }
             (_, Instruction::Continue(result)) => {
-                self.traversal_log.nodes.insert(edge.target.get_id());
-                self.traversal_log.nodes.insert(edge.origin.get_id());
-                self.traversal_log
-                    .edges
-                    .insert((edge.origin.get_id(), edge.target.get_id()));
+                self.traversal_log.nodes.insert(target.clone());
+                self.traversal_log.nodes.insert(origin.clone());
+                self.traversal_log.edges.insert((origin, target));
 
                 let mut child_meta = meta;
                 child_meta.depth += 1;
-                edge.target.with_edges_iter(|edges| {
-                    edges
-                        .cloned()
-                        .for_each(|edge| self.queue.push_back((child_meta.clone(), edge)))
-                });
+                edge.target_node()
+                    .for_each_edge(|edge| self.queue.push_back((child_meta.clone(), edge)));
                 Some(result)
             }
         }
@@ -412,25 +444,25 @@ where
 
     pub fn drive_node<F, R>(&mut self, mut test_fn: F) -> Option<R>
     where
-        F: FnMut(TraversalMeta<T::Id>, Node<T>) -> Instruction<R>,
+        F: FnMut(TraversalMeta<N::Id>, N) -> Instruction<R>,
     {
-        self.drive_edge(|meta, edge| test_fn(meta, edge.target))
+        self.drive_edge(|meta, edge| test_fn(meta, edge.target_node().clone()))
     }
 }
 
-pub struct GraphIterator<T, F, R>
+pub struct GraphIterator<N, F, R>
 where
-    T: GraphDefinition,
-    F: FnMut(TraversalMeta<T::Id>, Edge<T>) -> Instruction<R>,
+    N: TraversableNode,
+    F: FnMut(TraversalMeta<N::Id>, N::Edge) -> Instruction<R>,
 {
-    iterator: TraverseGraph<T, Started>,
+    iterator: TraverseGraph<N, Started>,
     test_fn: F,
 }
 
-impl<T, F, R> Iterator for GraphIterator<T, F, R>
+impl<N, F, R> Iterator for GraphIterator<N, F, R>
 where
-    T: GraphDefinition,
-    F: FnMut(TraversalMeta<T::Id>, Edge<T>) -> Instruction<R>,
+    N: TraversableNode,
+    F: FnMut(TraversalMeta<N::Id>, N::Edge) -> Instruction<R>,
 {
     type Item = R;
 
@@ -443,8 +475,8 @@ where
 #[derive(Clone)]
 pub struct TraversalMeta<I> {
     depth: usize,
-    path: Rc<Cell<Vec<I>>>,
-    any_meta: Rc<AnymapCell>,
+    path: RefCount<SharedCell<Vec<I>>>,
+    any_meta: RefCount<AnymapCell>,
 }
 
 impl<I> TraversalMeta<I>
@@ -455,21 +487,19 @@ where
         Self {
             depth,
             path: Default::default(),
-            any_meta: Rc::new(Default::default()),
+            any_meta: RefCount::new(Default::default()),
         }
     }
 
     pub fn include_path(&mut self, target: I) {
-        let mut path = self.path.take();
-        path.push(target);
-        self.path.set(path);
+        self.path.with_mut(|path| path.push(target));
     }
 
-    pub fn annotate<A: 'static>(&self, annotation: A) -> Option<A> {
+    pub fn annotate<A: Shareable>(&self, annotation: A) -> Option<A> {
         self.any_meta.insert(annotation)
     }
 
-    pub fn get_annotation<A: Clone + 'static>(&self) -> Option<A> {
+    pub fn get_annotation<A: Clone + Shareable>(&self) -> Option<A> {
         self.any_meta.get()
     }
 
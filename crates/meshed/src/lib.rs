@@ -21,6 +21,7 @@ pub(crate) mod anymap;
 pub mod extract;
 pub mod graph;
 pub mod identify;
+pub mod rc;
 pub mod prelude {
     pub use crate::extract::*;
     pub use crate::identify::*;
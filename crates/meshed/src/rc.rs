@@ -0,0 +1,85 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Shared ownership for graph nodes. By default graphs are built from `Rc`
+//! and `Cell` and can only be used from a single thread. The `sync` feature
+//! swaps them for `Arc` and `RwLock` so graphs can be traversed in parallel.
+
+#[cfg(not(feature = "sync"))]
+use std::cell::Cell;
+#[cfg(not(feature = "sync"))]
+use std::rc::Rc;
+
+#[cfg(feature = "sync")]
+use std::sync::{Arc, RwLock};
+
+#[cfg(not(feature = "sync"))]
+pub type RefCount<T> = Rc<T>;
+
+#[cfg(feature = "sync")]
+pub type RefCount<T> = Arc<T>;
+
+/// Values that can be attached to nodes as annotations. With the `sync`
+/// feature annotations must be thread safe.
+#[cfg(not(feature = "sync"))]
+pub trait Shareable: 'static {}
+#[cfg(not(feature = "sync"))]
+impl<T: 'static> Shareable for T {}
+
+#[cfg(feature = "sync")]
+pub trait Shareable: Send + Sync + 'static {}
+#[cfg(feature = "sync")]
+impl<T: Send + Sync + 'static> Shareable for T {}
+
+/// Interior mutability for the parts of a node that change after the graph
+/// is built (edges and annotations).
+#[cfg(not(feature = "sync"))]
+#[derive(Default)]
+pub(crate) struct SharedCell<T>(Cell<T>);
+
+#[cfg(not(feature = "sync"))]
+impl<T: Default> SharedCell<T> {
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let value = self.0.take();
+        let output = f(&value);
+        self.0.set(value);
+        output
+    }
+
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut value = self.0.take();
+        let output = f(&mut value);
+        self.0.set(value);
+        output
+    }
+}
+
+#[cfg(feature = "sync")]
+#[derive(Default)]
+pub(crate) struct SharedCell<T>(RwLock<T>);
+
+#[cfg(feature = "sync")]
+impl<T: Default> SharedCell<T> {
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let value = self.0.read().expect("Graph lock was poisoned");
+        f(&value)
+    }
+
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut value = self.0.write().expect("Graph lock was poisoned");
+        f(&mut value)
+    }
+}
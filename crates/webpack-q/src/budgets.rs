@@ -0,0 +1,443 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Size budgets for entrypoints and assets, read from a toml file.
+//!
+//! ```toml
+//! # Every entrypoint matching the glob
+//! [[entrypoint]]
+//! name = "*"
+//! # Size of the chunks loaded synchronously with the entrypoint
+//! max_initial_size = "250 KiB"
+//! # Size of each chunk the entrypoint loads asynchronously
+//! max_async_chunk_size = "100 KiB"
+//! # Number of modules in the synchronously loaded chunks
+//! max_modules = 400
+//!
+//! [[asset]]
+//! name = "*.js"
+//! max_size = 150000
+//! ```
+//!
+//! Sizes are either a number of bytes or a string with one of the units
+//! B, KB, KiB, MB, MiB, GB or GiB.
+
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+use meshed::graph::GraphDefinition;
+use meshed::prelude::*;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use thiserror::Error;
+use webpack_stats::asset::Asset;
+use webpack_stats::chunk::{Chunk, ChunkId, ChunkModules, Chunks};
+use webpack_stats::entry::Entrypoint;
+use webpack_stats::SizeBytes;
+use wildmatch::WildMatch;
+
+use crate::graphs::ChunkLoadGraph;
+use crate::operations::{initial_chunks, initial_load_size, reachable_chunks};
+
+#[derive(Debug, Error)]
+pub enum BudgetError {
+    #[error("Invalid budget file")]
+    Parse(#[from] toml::de::Error),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budgets {
+    #[serde(default, rename = "entrypoint")]
+    pub entrypoints: Vec<EntrypointBudget>,
+    #[serde(default, rename = "asset")]
+    pub assets: Vec<AssetBudget>,
+}
+
+impl Budgets {
+    pub fn from_toml(source: &str) -> Result<Self, BudgetError> {
+        Ok(toml::from_str(source)?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EntrypointBudget {
+    /// Glob over entrypoint names
+    pub name: Glob,
+    pub max_initial_size: Option<BudgetSize>,
+    pub max_async_chunk_size: Option<BudgetSize>,
+    pub max_modules: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssetBudget {
+    /// Glob over asset file names
+    pub name: Glob,
+    pub max_size: Option<BudgetSize>,
+}
+
+#[derive(Debug)]
+pub struct Glob {
+    pattern: String,
+    matcher: WildMatch,
+}
+
+impl Glob {
+    pub fn matches(&self, name: &str) -> bool {
+        self.matcher.matches(name)
+    }
+}
+
+impl Display for Glob {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl<'de> Deserialize<'de> for Glob {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let pattern = String::deserialize(deserializer)?;
+        Ok(Self {
+            matcher: WildMatch::new(&pattern),
+            pattern,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct BudgetSize(pub SizeBytes);
+
+impl BudgetSize {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let split = value
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(value.len());
+        let (amount, unit) = value.split_at(split);
+        let amount: f32 = amount.parse().ok()?;
+        let multiplier = match unit.trim().to_ascii_lowercase().as_str() {
+            "" | "b" => 1.0,
+            "kb" => 1000.0,
+            "kib" => 1024.0,
+            "mb" => 1000.0 * 1000.0,
+            "mib" => 1024.0 * 1024.0,
+            "gb" => 1000.0 * 1000.0 * 1000.0,
+            "gib" => 1024.0 * 1024.0 * 1024.0,
+            _ => return None,
+        };
+
+        Some(Self(SizeBytes(amount * multiplier)))
+    }
+}
+
+impl<'de> Deserialize<'de> for BudgetSize {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum SizeShape {
+            Bytes(f32),
+            Text(String),
+        }
+
+        match SizeShape::deserialize(deserializer)? {
+            SizeShape::Bytes(bytes) => Ok(Self(SizeBytes(bytes))),
+            SizeShape::Text(text) => Self::parse(&text)
+                .ok_or_else(|| D::Error::custom(format!("invalid size {:?}", text))),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum Amount {
+    Size(SizeBytes),
+    Count(usize),
+}
+
+impl Display for Amount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Amount::Size(size) => Display::fmt(size, f),
+            Amount::Count(count) => Display::fmt(count, f),
+        }
+    }
+}
+
+/// A single measurement compared against its budget
+#[derive(Debug)]
+pub struct BudgetCheck {
+    /// e.g. `entrypoint index` or `asset main.js`
+    pub subject: String,
+    /// e.g. `initial size`
+    pub measure: String,
+    pub actual: Amount,
+    pub limit: Amount,
+}
+
+impl BudgetCheck {
+    pub fn passed(&self) -> bool {
+        self.actual <= self.limit
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BudgetReport {
+    pub checks: Vec<BudgetCheck>,
+}
+
+impl BudgetReport {
+    pub fn violations(&self) -> impl Iterator<Item = &BudgetCheck> {
+        self.checks.iter().filter(|check| !check.passed())
+    }
+
+    pub fn passed(&self) -> bool {
+        self.violations().next().is_none()
+    }
+}
+
+impl Display for BudgetReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for check in self.checks.iter() {
+            let status = if check.passed() { "ok" } else { "FAIL" };
+            writeln!(
+                f,
+                "[{:>4}] {} {}: {} (limit {})",
+                status, check.subject, check.measure, check.actual, check.limit
+            )?;
+        }
+        writeln!(
+            f,
+            "{} of {} budgets exceeded",
+            self.violations().count(),
+            self.checks.len()
+        )
+    }
+}
+
+/// Measure every entrypoint and asset that matches a budget
+pub fn check_budgets<E, A, C, Cv>(
+    budgets: &Budgets,
+    entrypoints: &[&E],
+    assets: &[A],
+    chunks: &C,
+) -> BudgetReport
+where
+    E: Entrypoint,
+    A: Asset,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+{
+    let graph = ChunkLoadGraph::build_graph(chunks);
+    let mut report = BudgetReport::default();
+
+    for budget in budgets.entrypoints.iter() {
+        let matching: Vec<_> = entrypoints
+            .iter()
+            .filter(|entrypoint| budget.name.matches(entrypoint.name()))
+            .collect();
+        if matching.is_empty() {
+            tracing::warn!("No entrypoint matches budget {}", budget.name);
+        }
+
+        for entrypoint in matching {
+            let subject = format!("entrypoint {}", entrypoint.name());
+            let initial = initial_chunks(&graph, entrypoint.chunks());
+
+            if let Some(limit) = budget.max_initial_size {
+                report.checks.push(BudgetCheck {
+                    subject: subject.clone(),
+                    measure: "initial size".to_string(),
+                    actual: Amount::Size(initial_load_size(&graph, entrypoint.chunks())),
+                    limit: Amount::Size(limit.0),
+                });
+            }
+
+            if let Some(limit) = budget.max_modules {
+                let modules: HashSet<_> = initial
+                    .iter()
+                    .filter_map(|chunk| chunks.query(chunk))
+                    .flat_map(|chunk| ExtractData::<ChunkModules>::extract_data(chunk))
+                    .collect();
+                report.checks.push(BudgetCheck {
+                    subject: subject.clone(),
+                    measure: "initial modules".to_string(),
+                    actual: Amount::Count(modules.len()),
+                    limit: Amount::Count(limit),
+                });
+            }
+
+            if let Some(limit) = budget.max_async_chunk_size {
+                let mut async_chunks: Vec<ChunkId> = reachable_chunks(&graph, entrypoint.chunks())
+                    .into_iter()
+                    .filter(|chunk| !initial.contains(chunk))
+                    .collect();
+                async_chunks.sort();
+
+                for chunk in async_chunks {
+                    let size = graph.query(&chunk).map(|node| node.node_data().1);
+                    report.checks.push(BudgetCheck {
+                        subject: subject.clone(),
+                        measure: format!("async chunk {} size", chunk),
+                        actual: Amount::Size(size.unwrap_or_default()),
+                        limit: Amount::Size(limit.0),
+                    });
+                }
+            }
+        }
+    }
+
+    for budget in budgets.assets.iter() {
+        let limit = match budget.max_size {
+            None => continue,
+            Some(limit) => limit,
+        };
+        let matching: Vec<_> = assets
+            .iter()
+            .filter(|asset| budget.name.matches(asset.name()))
+            .collect();
+        if matching.is_empty() {
+            tracing::warn!("No asset matches budget {}", budget.name);
+        }
+
+        for asset in matching {
+            report.checks.push(BudgetCheck {
+                subject: format!("asset {}", asset.name()),
+                measure: "size".to_string(),
+                actual: Amount::Size(asset.size()),
+                limit: Amount::Size(limit.0),
+            });
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webpack_stats::v5::Stats;
+
+    static SOURCE_FILE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../webpack-stats/test_projects/v5/compilation-stats.json"
+    ));
+
+    fn parse(value: &str) -> Option<f32> {
+        BudgetSize::parse(value).map(|size| size.0 .0)
+    }
+
+    #[test]
+    fn parses_sizes_with_units() {
+        assert_eq!(parse("250 KiB"), Some(256000.0));
+        assert_eq!(parse("1.5kb"), Some(1500.0));
+        assert_eq!(parse("2 MiB"), Some(2097152.0));
+        assert_eq!(parse("42"), Some(42.0));
+        assert_eq!(parse(" 42 B "), Some(42.0));
+        assert_eq!(parse("10 parsecs"), None);
+        assert_eq!(parse("KiB"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn rejects_invalid_budget_files() {
+        assert!(
+            Budgets::from_toml("[[asset]]\nname = \"*.js\"\nmax_size = \"1 lightyear\"").is_err()
+        );
+        assert!(Budgets::from_toml("[[asset]]\nname = \"*.js\"\nmax_sise = 1").is_err());
+    }
+
+    #[test]
+    fn checks_budgets_against_the_compilation() {
+        let stats: Stats = serde_json::from_str(SOURCE_FILE).unwrap();
+        let entrypoints: Vec<_> = stats.entrypoints.values().collect();
+        let budgets = Budgets::from_toml(
+            r#"
+            [[entrypoint]]
+            name = "index"
+            max_initial_size = "10 KiB"
+            max_async_chunk_size = 700
+
+            [[entrypoint]]
+            name = "entry*"
+            max_initial_size = "10 KiB"
+
+            [[entrypoint]]
+            name = "missing"
+            max_modules = 1
+
+            [[asset]]
+            name = "*.js"
+            max_size = 3000
+            "#,
+        )
+        .unwrap();
+
+        let report = check_budgets(&budgets, &entrypoints, &stats.assets, &stats.chunks);
+        let mut checks: Vec<_> = report
+            .checks
+            .iter()
+            .map(|check| {
+                (
+                    format!("{} {}", check.subject, check.measure),
+                    check.actual,
+                    check.passed(),
+                )
+            })
+            .collect();
+        checks.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let size = |bytes: f32| Amount::Size(SizeBytes(bytes));
+        assert_eq!(
+            checks,
+            vec![
+                ("asset 1.js size".to_string(), size(122.0), true),
+                ("asset 288.js size".to_string(), size(117.0), true),
+                ("asset 960.js size".to_string(), size(197.0), true),
+                ("asset entryTwo.js size".to_string(), size(2634.0), true),
+                ("asset index.js size".to_string(), size(3193.0), false),
+                (
+                    "entrypoint entryTwo initial size".to_string(),
+                    size(8123.0),
+                    true
+                ),
+                (
+                    "entrypoint index async chunk 1 size".to_string(),
+                    size(43.0),
+                    true
+                ),
+                (
+                    "entrypoint index async chunk 960 size".to_string(),
+                    size(741.0),
+                    false
+                ),
+                (
+                    "entrypoint index initial size".to_string(),
+                    size(10741.0),
+                    false
+                ),
+            ]
+        );
+        assert!(!report.passed());
+        assert_eq!(report.violations().count(), 3);
+    }
+}
@@ -0,0 +1,333 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Gzip and brotli sizes of what users actually download.
+//!
+//! When the output directory of a compilation still exists on disk the
+//! emitted files are compressed directly. Otherwise the sizes are estimated
+//! by compressing the module sources embedded in the stats file. Each
+//! algorithm is only available when the matching crate feature is enabled.
+
+use crate::graphs::ModuleParentGraph;
+use meshed::graph::Inverted;
+use meshed::prelude::*;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Add;
+use std::path::{Path, PathBuf};
+use webpack_stats::chunk::{Chunk, ChunkModules, Files};
+use webpack_stats::module::{Module, ModuleIdentifier, Modules};
+use webpack_stats::SizeBytes;
+
+/// Compressed sizes. `None` when the size could not be computed, either
+/// because the algorithm is disabled or because there was nothing to
+/// compress.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize)]
+pub struct CompressedSize {
+    pub gzip: Option<SizeBytes>,
+    pub brotli: Option<SizeBytes>,
+}
+
+impl CompressedSize {
+    pub fn of(bytes: &[u8]) -> Self {
+        Self {
+            gzip: gzip_size(bytes),
+            brotli: brotli_size(bytes),
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        self.gzip.is_none() && self.brotli.is_none()
+    }
+}
+
+/// A sum is only known when both sides are known
+impl Add for CompressedSize {
+    type Output = CompressedSize;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        fn add(lhs: Option<SizeBytes>, rhs: Option<SizeBytes>) -> Option<SizeBytes> {
+            Some(lhs? + rhs?)
+        }
+
+        Self {
+            gzip: add(self.gzip, rhs.gzip),
+            brotli: add(self.brotli, rhs.brotli),
+        }
+    }
+}
+
+impl Display for CompressedSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.gzip, self.brotli) {
+            (None, None) => write!(f, "unavailable"),
+            (Some(gzip), None) => write!(f, "{} gzip", gzip),
+            (None, Some(brotli)) => write!(f, "{} brotli", brotli),
+            (Some(gzip), Some(brotli)) => write!(f, "{} gzip, {} brotli", gzip, brotli),
+        }
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_size(bytes: &[u8]) -> Option<SizeBytes> {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(bytes).ok()?;
+    let compressed = encoder.finish().ok()?;
+    Some(SizeBytes(compressed.len() as f32))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_size(_bytes: &[u8]) -> Option<SizeBytes> {
+    None
+}
+
+#[cfg(feature = "brotli")]
+fn brotli_size(bytes: &[u8]) -> Option<SizeBytes> {
+    use std::io::Write;
+
+    // Quality 11 is what build tools use to precompress static assets
+    let mut compressed = Vec::new();
+    {
+        let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 11, 22);
+        writer.write_all(bytes).ok()?;
+    }
+    Some(SizeBytes(compressed.len() as f32))
+}
+
+#[cfg(not(feature = "brotli"))]
+fn brotli_size(_bytes: &[u8]) -> Option<SizeBytes> {
+    None
+}
+
+/// Raw and compressed size of a module. Annotated on module graph nodes so
+/// that serialized graphs can include them.
+#[derive(Debug, Default, Copy, Clone, Serialize)]
+pub struct ModuleSize {
+    pub size: SizeBytes,
+    #[serde(flatten)]
+    pub compressed: CompressedSize,
+}
+
+/// Computes compressed sizes of chunks and modules of a single compilation
+pub struct CompressionEstimator<'a> {
+    output_path: Option<PathBuf>,
+    sources: HashMap<ModuleIdentifier, &'a str>,
+    /// Module sizes are requested again by every graph that is annotated,
+    /// compress each source only once
+    modules: RefCell<HashMap<ModuleIdentifier, CompressedSize>>,
+    annotate_compressed: bool,
+}
+
+impl<'a> CompressionEstimator<'a> {
+    /// `output_path` is only used when it is a directory that exists
+    pub fn new<M, Mv>(output_path: &Path, modules: &'a M) -> Self
+    where
+        M: Modules<Mv>,
+        Mv: Module + 'a,
+    {
+        let output_path = if output_path.is_dir() {
+            Some(output_path.to_path_buf())
+        } else {
+            tracing::debug!(
+                "Output path {:?} does not exist. Estimating compressed sizes from module sources",
+                output_path
+            );
+            None
+        };
+
+        let sources = modules
+            .all()
+            .into_iter()
+            .filter_map(|module| Some((module.get_id(), module.source()?)))
+            .collect();
+
+        Self {
+            output_path,
+            sources,
+            modules: RefCell::default(),
+            annotate_compressed: false,
+        }
+    }
+
+    /// Also compress the source of every module [`Self::annotate_modules`]
+    /// annotates. Off by default, compressing every module of a large
+    /// compilation takes a while
+    pub fn with_module_estimates(mut self, enabled: bool) -> Self {
+        self.annotate_compressed = enabled;
+        self
+    }
+
+    /// Compressed size of a module's source
+    pub fn module(&self, id: &ModuleIdentifier) -> CompressedSize {
+        let source = match self.sources.get(id) {
+            None => return CompressedSize::default(),
+            Some(source) => source,
+        };
+        *self
+            .modules
+            .borrow_mut()
+            .entry(id.clone())
+            .or_insert_with(|| CompressedSize::of(source.as_bytes()))
+    }
+
+    /// Compressed size of the files emitted for a chunk. Falls back to the
+    /// sources of the chunk's modules when the files cannot be read.
+    pub fn chunk<Cv: Chunk>(&self, chunk: &Cv) -> CompressedSize {
+        let files: Files = chunk.extract_data();
+        if let Some(size) = self.emitted_files(&files) {
+            return size;
+        }
+
+        let modules: ChunkModules = chunk.extract_data();
+        let sources = modules
+            .iter()
+            .filter_map(|id| self.sources.get(id))
+            .copied()
+            .collect::<Vec<_>>();
+
+        if sources.is_empty() {
+            return CompressedSize::default();
+        }
+        CompressedSize::of(sources.join("\n").as_bytes())
+    }
+
+    fn emitted_files(&self, files: &Files) -> Option<CompressedSize> {
+        let output_path = self.output_path.as_ref()?;
+        if files.0.is_empty() {
+            return None;
+        }
+
+        files
+            .0
+            .iter()
+            .map(|file| {
+                let contents = std::fs::read(output_path.join(file)).ok()?;
+                Some(CompressedSize::of(&contents))
+            })
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .reduce(Add::add)
+    }
+
+    /// Annotate every node of a module graph with its [`ModuleSize`]. The
+    /// compressed size is unknown unless [`Self::with_module_estimates`] is
+    /// enabled
+    pub fn annotate_modules<M, Mv>(&self, graph: &Inverted<ModuleParentGraph>, modules: &M)
+    where
+        M: Modules<Mv>,
+        Mv: Module,
+    {
+        for node in graph.inner().all_nodes() {
+            let id = node.get_id();
+            let size = match modules.query(&id) {
+                Some(module) if module.get_id() == id => module.extract_data(),
+                _ => continue,
+            };
+
+            let compressed = if self.annotate_compressed {
+                self.module(&id)
+            } else {
+                CompressedSize::default()
+            };
+            node.annotate(ModuleSize { size, compressed });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webpack_stats::chunk::ChunkId;
+    use webpack_stats::v4::Stats;
+
+    static SOURCE_FILE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../webpack-stats/test_projects/v4/compilation-stats.json"
+    ));
+
+    #[test]
+    fn sums_are_only_known_when_both_sides_are() {
+        let known = CompressedSize {
+            gzip: Some(SizeBytes(10.0)),
+            brotli: Some(SizeBytes(8.0)),
+        };
+        let gzip_only = CompressedSize {
+            gzip: Some(SizeBytes(5.0)),
+            brotli: None,
+        };
+
+        assert_eq!(
+            known + known,
+            CompressedSize {
+                gzip: Some(SizeBytes(20.0)),
+                brotli: Some(SizeBytes(16.0)),
+            }
+        );
+        assert_eq!(
+            known + gzip_only,
+            CompressedSize {
+                gzip: Some(SizeBytes(15.0)),
+                brotli: None,
+            }
+        );
+        assert!((known + CompressedSize::default()).is_unknown());
+    }
+
+    #[test]
+    fn estimates_from_module_sources_without_output_path() {
+        let stats: Stats = serde_json::from_str(SOURCE_FILE).unwrap();
+        let estimator = CompressionEstimator::new(Path::new("does/not/exist"), &stats.modules);
+        let source = |name: &str| {
+            stats
+                .modules
+                .iter()
+                .find(|module| module.label().0.ends_with(name))
+                .and_then(|module| module.source())
+                .unwrap()
+        };
+
+        let entry_two = stats.chunks.query(&ChunkId::from(1)).unwrap();
+        let joined = [source("common.js"), source("entry_two.js")].join("\n");
+        assert_eq!(
+            estimator.chunk(entry_two),
+            CompressedSize::of(joined.as_bytes())
+        );
+
+        let common = stats
+            .modules
+            .iter()
+            .find(|module| module.label().0.ends_with("common.js"))
+            .unwrap();
+        let expected = CompressedSize::of(source("common.js").as_bytes());
+        assert_eq!(estimator.module(&common.get_id()), expected);
+        // Served from the cache the second time around
+        assert_eq!(estimator.module(&common.get_id()), expected);
+
+        // Concatenated modules have no source of their own
+        let concatenated = stats
+            .modules
+            .iter()
+            .find(|module| !module.concatenated().is_empty())
+            .unwrap();
+        assert!(estimator.module(&concatenated.get_id()).is_unknown());
+    }
+}
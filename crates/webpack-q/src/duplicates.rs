@@ -0,0 +1,361 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Packages from `node_modules` that are bundled more than once.
+//!
+//! A copy of a package is identified by the directory it is installed in, so
+//! `node_modules/lodash` and `node_modules/a/node_modules/lodash` are two
+//! copies of `lodash`.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use meshed::graph::GraphDefinition;
+use webpack_stats::chunk::{Chunk, ChunkId, Chunks};
+use webpack_stats::entry::Entrypoint;
+use webpack_stats::module::{Module, ModuleChunks, ModuleIdentifier, Modules};
+use webpack_stats::SizeBytes;
+
+use crate::graphs::ChunkLoadGraph;
+use crate::operations::reachable_chunks;
+
+const NODE_MODULES: &str = "node_modules/";
+
+/// The package a module path belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackagePath {
+    /// e.g. `@babel/runtime`
+    pub name: String,
+    /// Directory the package is installed in, e.g.
+    /// `./node_modules/a/node_modules/@babel/runtime`
+    pub install_path: String,
+}
+
+impl PackagePath {
+    /// Parse the innermost `node_modules/<package>` of a module path.
+    /// Loaders and queries are ignored. Returns `None` for modules that are
+    /// not in `node_modules`.
+    pub fn parse(path: &str) -> Option<Self> {
+        let resource = path.rsplit('!').next()?.replace('\\', "/");
+        let package_start = resource.rfind(NODE_MODULES)? + NODE_MODULES.len();
+
+        let mut segments = resource[package_start..].split('/');
+        let first = segments.next().filter(|segment| !segment.is_empty())?;
+        let name_len = if first.starts_with('@') {
+            let second = segments.next().filter(|segment| !segment.is_empty())?;
+            first.len() + 1 + second.len()
+        } else {
+            first.len()
+        };
+        let package_end = package_start + name_len;
+
+        Some(Self {
+            name: resource[package_start..package_end].to_string(),
+            install_path: resource[..package_end].to_string(),
+        })
+    }
+}
+
+/// A single installed copy of a package
+#[derive(Debug, Default)]
+pub struct PackageCopy {
+    pub install_path: String,
+    pub size: SizeBytes,
+    pub modules: usize,
+    pub chunks: BTreeSet<ChunkId>,
+    pub entrypoints: BTreeSet<String>,
+}
+
+#[derive(Debug)]
+pub struct DuplicatePackage {
+    pub name: String,
+    /// Largest copy first
+    pub copies: Vec<PackageCopy>,
+}
+
+impl DuplicatePackage {
+    /// Bytes that would be saved if only the largest copy was bundled
+    pub fn wasted(&self) -> SizeBytes {
+        self.copies
+            .iter()
+            .skip(1)
+            .fold(SizeBytes::default(), |acc, copy| acc + copy.size)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DuplicatesReport {
+    /// Most wasted bytes first
+    pub packages: Vec<DuplicatePackage>,
+}
+
+impl DuplicatesReport {
+    pub fn wasted(&self) -> SizeBytes {
+        self.packages
+            .iter()
+            .fold(SizeBytes::default(), |acc, package| acc + package.wasted())
+    }
+}
+
+impl Display for DuplicatesReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.packages.is_empty() {
+            return writeln!(f, "No duplicate packages");
+        }
+
+        for package in self.packages.iter() {
+            writeln!(
+                f,
+                "{} ({} copies, {} wasted)",
+                package.name,
+                package.copies.len(),
+                package.wasted()
+            )?;
+            for copy in package.copies.iter() {
+                writeln!(
+                    f,
+                    "├── {} ({}, {} modules)",
+                    copy.install_path, copy.size, copy.modules
+                )?;
+                write!(f, "│     chunks: ")?;
+                write_list(f, copy.chunks.iter())?;
+                write!(f, "│     entrypoints: ")?;
+                write_list(f, copy.entrypoints.iter())?;
+            }
+        }
+
+        writeln!(
+            f,
+            "{} duplicated packages, {} wasted",
+            self.packages.len(),
+            self.wasted()
+        )
+    }
+}
+
+fn write_list<T: Display>(
+    f: &mut Formatter<'_>,
+    items: impl Iterator<Item = T>,
+) -> std::fmt::Result {
+    let items = items.map(|item| item.to_string()).collect::<Vec<_>>();
+    if items.is_empty() {
+        writeln!(f, "none")
+    } else {
+        writeln!(f, "{}", items.join(", "))
+    }
+}
+
+/// Group every module inside `node_modules` by package and installed copy
+/// and report packages that have more than one copy.
+///
+/// Concatenated modules are attributed to the packages of the modules they
+/// contain. Modules that are not emitted in any chunk are left out. A copy is
+/// carried by an entrypoint when any of its chunks can be loaded by the
+/// entrypoint, synchronously or not.
+pub fn find_duplicate_packages<E, M, Mv, C, Cv>(
+    entrypoints: &[&E],
+    modules: &M,
+    chunks: &C,
+) -> DuplicatesReport
+where
+    E: Entrypoint,
+    M: Modules<Mv>,
+    Mv: Module,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+{
+    let graph = ChunkLoadGraph::build_graph(chunks);
+    let mut entrypoints_by_chunk: HashMap<ChunkId, BTreeSet<String>> = HashMap::new();
+    for entrypoint in entrypoints {
+        for chunk in reachable_chunks(&graph, entrypoint.chunks()) {
+            entrypoints_by_chunk
+                .entry(chunk)
+                .or_default()
+                .insert(entrypoint.name().to_string());
+        }
+    }
+
+    let mut packages: BTreeMap<String, BTreeMap<String, PackageCopy>> = BTreeMap::new();
+    // Modules can be both concatenated and emitted on their own, and runtime
+    // modules are listed once per chunk. Only count their size once
+    let mut seen: HashSet<ModuleIdentifier> = HashSet::new();
+    for module in modules.top_level() {
+        let module_chunks: ModuleChunks = module.extract_data();
+        if module_chunks.is_empty() {
+            continue;
+        }
+        let mut record = |module: &Mv| {
+            let package = match PackagePath::parse(&module.label().0)
+                .or_else(|| PackagePath::parse(&module.get_id().0))
+            {
+                None => return,
+                Some(package) => package,
+            };
+
+            let copy = packages
+                .entry(package.name)
+                .or_default()
+                .entry(package.install_path.clone())
+                .or_insert_with(|| PackageCopy {
+                    install_path: package.install_path,
+                    ..Default::default()
+                });
+            if seen.insert(module.get_id()) {
+                copy.size = copy.size + module.extract_data();
+                copy.modules += 1;
+            }
+            for chunk in module_chunks.iter() {
+                copy.chunks.insert(chunk.clone());
+                if let Some(names) = entrypoints_by_chunk.get(chunk) {
+                    copy.entrypoints.extend(names.iter().cloned());
+                }
+            }
+        };
+
+        let concatenated = module.concatenated();
+        if concatenated.is_empty() {
+            record(module);
+        } else {
+            let mut stack = concatenated;
+            while let Some(module) = stack.pop() {
+                let children = module.concatenated();
+                if children.is_empty() {
+                    record(module);
+                } else {
+                    stack.extend(children);
+                }
+            }
+        }
+    }
+
+    let mut duplicates = packages
+        .into_iter()
+        .filter(|(_, copies)| copies.len() > 1)
+        .map(|(name, copies)| {
+            let mut copies = copies.into_values().collect::<Vec<_>>();
+            copies.sort_by(|a, b| b.size.partial_cmp(&a.size).unwrap_or(Ordering::Equal));
+            DuplicatePackage { name, copies }
+        })
+        .collect::<Vec<_>>();
+    duplicates.sort_by(|a, b| {
+        b.wasted()
+            .partial_cmp(&a.wasted())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    DuplicatesReport {
+        packages: duplicates,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webpack_stats::v5::Stats;
+
+    /// Two emitted installs of `lodash`, one of them concatenated into a
+    /// module of the async chunk, and an install that is not in any chunk
+    static SOURCE_FILE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../webpack-stats/test_projects/v5-duplicates/compilation-stats.json"
+    ));
+
+    #[test]
+    fn groups_copies_of_emitted_packages() {
+        let stats: Stats = serde_json::from_str(SOURCE_FILE).unwrap();
+        let mut entrypoints: Vec<_> = stats.entrypoints.values().collect();
+        entrypoints.sort_by_key(|entrypoint| entrypoint.name());
+
+        let report = find_duplicate_packages(&entrypoints, &stats.modules, &stats.chunks);
+        assert_eq!(report.packages.len(), 1);
+        let lodash = &report.packages[0];
+        assert_eq!(lodash.name, "lodash");
+
+        let copies: Vec<_> = lodash
+            .copies
+            .iter()
+            .map(|copy| {
+                (
+                    copy.install_path.as_str(),
+                    copy.size,
+                    copy.modules,
+                    copy.chunks
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>(),
+                    copy.entrypoints.iter().cloned().collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            copies,
+            vec![
+                (
+                    "./node_modules/lodash",
+                    SizeBytes(300.0),
+                    2,
+                    vec!["1".to_string(), "3".to_string()],
+                    vec!["admin".to_string(), "app".to_string()],
+                ),
+                (
+                    "./node_modules/ui/node_modules/lodash",
+                    SizeBytes(120.0),
+                    1,
+                    vec!["2".to_string()],
+                    vec!["app".to_string()],
+                ),
+            ]
+        );
+        assert_eq!(report.wasted(), SizeBytes(120.0));
+    }
+
+    fn parse(path: &str) -> Option<(String, String)> {
+        PackagePath::parse(path).map(|package| (package.name, package.install_path))
+    }
+
+    #[test]
+    fn parses_packages_from_module_paths() {
+        assert_eq!(
+            parse("./node_modules/lodash/lodash.js"),
+            Some(("lodash".into(), "./node_modules/lodash".into()))
+        );
+        assert_eq!(
+            parse("./node_modules/@babel/runtime/helpers/esm/extends.js + 2 modules"),
+            Some((
+                "@babel/runtime".into(),
+                "./node_modules/@babel/runtime".into()
+            ))
+        );
+        assert_eq!(
+            parse("../node_modules/a/node_modules/react/index.js"),
+            Some((
+                "react".into(),
+                "../node_modules/a/node_modules/react".into()
+            ))
+        );
+        assert_eq!(
+            parse("./node_modules/babel-loader/lib/index.js!./src/index.js"),
+            None
+        );
+        assert_eq!(
+            parse("C:\\app\\node_modules\\lodash\\map.js"),
+            Some(("lodash".into(), "C:/app/node_modules/lodash".into()))
+        );
+        assert_eq!(parse("./node_modules/@scope"), None);
+        assert_eq!(parse("./src/index.js"), None);
+    }
+}
@@ -16,11 +16,16 @@
 
 pub mod ser;
 
+use crate::compressed::ModuleSize;
+use meshed::graph::attributes::{Attributes, GraphAttributes};
+use meshed::graph::edge::Edge;
+use meshed::graph::graphviz::DotStyle;
+use meshed::graph::node::Node;
 use meshed::graph::GraphDefinition;
 use webpack_stats::chunk::{
     ChunkChild, ChunkChildren, ChunkId, ChunkInitial, ChunkModules, ChunkParentOrSibling, Files,
 };
-use webpack_stats::import::{ImportType, ResolvedModule};
+use webpack_stats::import::{ImportLocation, ImportType, ResolvedModule};
 use webpack_stats::module::{ModuleChunks, ModuleIdentifier, ModuleName};
 use webpack_stats::SizeBytes;
 
@@ -29,7 +34,7 @@ pub struct ModuleParentGraph;
 impl GraphDefinition for ModuleParentGraph {
     type Id = ModuleIdentifier;
     type Label = ModuleName;
-    type EdgeMeta = (ImportType, ResolvedModule);
+    type EdgeMeta = (ImportType, ResolvedModule, ImportLocation);
     type NodeData = ModuleChunks;
 }
 
@@ -58,3 +63,83 @@ impl GraphDefinition for ChunkLoadGraph {
     type EdgeMeta = ChunkChild;
     type NodeData = (ChunkChildren, SizeBytes, ChunkInitial, Files);
 }
+
+/// Modules are sized by their [`ModuleSize`] annotation and grouped by the
+/// chunk a traversal assigned them to
+impl DotStyle for ModuleParentGraph {
+    fn node_size(node: &Node<Self>) -> Option<f32> {
+        node.get_annotation::<ModuleSize>()
+            .map(|module_size| module_size.size.0)
+    }
+
+    fn node_cluster(node: &Node<Self>) -> Option<String> {
+        node.get_annotation::<ChunkId>()
+            .map(|chunk| format!("chunk {}", chunk))
+    }
+}
+
+/// Chunks are sized by their byte size
+impl DotStyle for ChunkLoadGraph {
+    fn node_size(node: &Node<Self>) -> Option<f32> {
+        Some(node.node_data().1 .0)
+    }
+}
+
+/// Dynamic imports are dashed
+impl GraphAttributes for ModuleParentGraph {
+    fn node_attributes(node: &Node<Self>) -> Attributes {
+        let mut attributes = vec![];
+        if let Some(chunk) = node.get_annotation::<ChunkId>() {
+            attributes.push(("chunk", chunk.to_string().into()));
+        }
+        let mut chunks: Vec<_> = node.node_data().iter().collect();
+        chunks.sort();
+        let chunks: Vec<_> = chunks.iter().map(ToString::to_string).collect();
+        attributes.push(("chunks", chunks.join(", ").into()));
+
+        if let Some(module_size) = node.get_annotation::<ModuleSize>() {
+            attributes.push(("size", module_size.size.0.into()));
+            if let Some(gzip) = module_size.compressed.gzip {
+                attributes.push(("gzip", gzip.0.into()));
+            }
+            if let Some(brotli) = module_size.compressed.brotli {
+                attributes.push(("brotli", brotli.0.into()));
+            }
+        }
+        attributes
+    }
+
+    fn edge_attributes(edge: &Edge<Self>) -> Attributes {
+        let (import_type, _, location) = edge.meta.as_ref();
+        let mut attributes = vec![("type", format!("{:?}", import_type).into())];
+        if !location.0.is_empty() {
+            attributes.push(("location", location.to_string().into()));
+        }
+        attributes
+    }
+
+    fn edge_dashed(edge: &Edge<Self>) -> bool {
+        matches!(edge.meta.0, ImportType::ImportDynamic)
+    }
+}
+
+/// Chunks that are not initial are loaded asynchronously, so the edges
+/// leading to them are dashed
+impl GraphAttributes for ChunkLoadGraph {
+    fn node_attributes(node: &Node<Self>) -> Attributes {
+        let (_, size, initial, files) = node.node_data();
+        vec![
+            ("size", size.0.into()),
+            ("initial", initial.0.into()),
+            ("files", files.0.join(", ").into()),
+        ]
+    }
+
+    fn edge_attributes(edge: &Edge<Self>) -> Attributes {
+        vec![("async", Self::edge_dashed(edge).into())]
+    }
+
+    fn edge_dashed(edge: &Edge<Self>) -> bool {
+        !edge.target.node_data().2 .0
+    }
+}
@@ -14,9 +14,10 @@
  * limitations under the License.
  */
 
-use crate::graphs::ModuleParentGraph;
+use crate::compressed::ModuleSize;
+use crate::graphs::{ChunkLoadGraph, ModuleParentGraph};
 use meshed::graph::node::Node;
-use meshed::graph::Inverted;
+use meshed::graph::{Graph, Inverted};
 use std::collections::HashSet;
 
 use meshed::prelude::*;
@@ -39,6 +40,10 @@ impl<T, K> GraphSerialization<T, K> {
             kind: Default::default(),
         }
     }
+
+    pub fn graph(&self) -> &T {
+        &self.graph
+    }
 }
 
 pub struct NodeEdge;
@@ -60,12 +65,13 @@ impl Serialize for ModuleEdgeSer {
             where
                 S: Serializer,
             {
-                let mut map = serializer.serialize_map(Some(3))?;
+                let size = self.0.get_annotation::<ModuleSize>();
+                let mut map = serializer.serialize_map(Some(if size.is_some() { 6 } else { 3 }))?;
                 map.serialize_key("id")?;
                 map.serialize_value(&self.0.get_id().to_string())?;
                 if let Some(value) = self.0.get_annotation::<ChunkId>() {
                     map.serialize_key("chunk")?;
-                    map.serialize_value(&value.0)?;
+                    map.serialize_value(&value)?;
                 } else {
                     map.serialize_key("chunk")?;
                     map.serialize_value(&None as &Option<()>)?;
@@ -73,6 +79,15 @@ impl Serialize for ModuleEdgeSer {
 
                 map.serialize_key("label")?;
                 map.serialize_value(&self.0.label().to_string())?;
+
+                if let Some(size) = size {
+                    map.serialize_key("size")?;
+                    map.serialize_value(&size.size)?;
+                    map.serialize_key("gzip")?;
+                    map.serialize_value(&size.compressed.gzip)?;
+                    map.serialize_key("brotli")?;
+                    map.serialize_value(&size.compressed.brotli)?;
+                }
                 map.end()
             }
         }
@@ -93,7 +108,7 @@ impl Serialize for ModuleEdgeSer {
             where
                 S: Serializer,
             {
-                let mut map = serializer.serialize_map(Some(4))?;
+                let mut map = serializer.serialize_map(Some(5))?;
 
                 map.serialize_key("source")?;
                 map.serialize_value(self.0.origin.get_id().0.as_ref())?;
@@ -108,6 +123,9 @@ impl Serialize for ModuleEdgeSer {
                 map.serialize_key("importer")?;
                 map.serialize_value(&self.0.meta.as_ref().1 .0)?;
 
+                map.serialize_key("loc")?;
+                map.serialize_value(self.0.meta.as_ref().2 .0.as_ref())?;
+
                 map.end()
             }
         }
@@ -132,3 +150,79 @@ impl Serialize for ModuleEdgeSer {
         map_serializer.end()
     }
 }
+
+type ChunkEdgeSer = GraphSerialization<Graph<ChunkLoadGraph>, NodeEdge>;
+
+/// Chunks are serialized in the same shape as modules so that the html
+/// viewer can show either. Every chunk is its own `chunk`
+impl Serialize for ChunkEdgeSer {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map_serializer = serializer.serialize_map(Some(2))?;
+
+        struct NodeSerializer(Node<ChunkLoadGraph>);
+
+        impl Serialize for NodeSerializer {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let (_, size, initial, files) = self.0.node_data();
+                let mut map = serializer.serialize_map(Some(6))?;
+                map.serialize_key("id")?;
+                map.serialize_value(&self.0.get_id().to_string())?;
+                map.serialize_key("chunk")?;
+                map.serialize_value(&self.0.get_id())?;
+                map.serialize_key("label")?;
+                map.serialize_value(&self.0.label().to_string())?;
+                map.serialize_key("size")?;
+                map.serialize_value(size)?;
+                map.serialize_key("initial")?;
+                map.serialize_value(&initial.0)?;
+                map.serialize_key("files")?;
+                map.serialize_value(&files.0)?;
+                map.end()
+            }
+        }
+
+        let nodes = self
+            .graph
+            .all_nodes()
+            .map(NodeSerializer)
+            .collect::<Vec<_>>();
+        map_serializer.serialize_key("nodes")?;
+        map_serializer.serialize_value(&nodes)?;
+
+        struct EdgeSerializer(meshed::graph::edge::Edge<ChunkLoadGraph>);
+
+        impl Serialize for EdgeSerializer {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let mut map = serializer.serialize_map(Some(3))?;
+                map.serialize_key("source")?;
+                map.serialize_value(&self.0.origin.get_id().to_string())?;
+                map.serialize_key("target")?;
+                map.serialize_value(&self.0.target.get_id().to_string())?;
+                map.serialize_key("async")?;
+                map.serialize_value(&!self.0.target.node_data().2 .0)?;
+                map.end()
+            }
+        }
+
+        let mut seen_set = HashSet::new();
+        let edges = self
+            .graph
+            .all_edges()
+            .filter(|edge| seen_set.insert(edge.get_id()))
+            .map(EdgeSerializer)
+            .collect::<Vec<_>>();
+
+        map_serializer.serialize_key("edges")?;
+        map_serializer.serialize_value(&edges)?;
+        map_serializer.end()
+    }
+}
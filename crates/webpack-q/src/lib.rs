@@ -24,4 +24,10 @@ pub mod prelude {
     pub use meshed::prelude::*;
 }
 
+pub mod budgets;
+pub mod compressed;
+pub mod duplicates;
 pub mod operations;
+pub mod query;
+pub mod session;
+pub mod treemap;
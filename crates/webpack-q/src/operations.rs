@@ -14,23 +14,33 @@
  * limitations under the License.
  */
 
+use crate::compressed::{CompressedSize, CompressionEstimator};
 use crate::graphs::{ChunkGraph, ChunkImportPathGraph, ChunkLoadGraph, ModuleParentGraph};
 use meshed::graph::traversal::{
-    traverse_graph, GraphTraversal, Instruction, Mode, Pathing, TraversalLog,
+    traverse_graph, GraphTraversal, Instruction, Mode, Pathing, TraversableNode, TraversalLog,
 };
 use meshed::prelude::*;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt::{write, Display, Formatter};
 
+use meshed::graph::arena::{ArenaGraph, ArenaNode};
+use meshed::graph::edge::Edge;
 use meshed::graph::node::Node;
 use meshed::graph::traversal::Mode::Acyclic;
 use meshed::graph::traversal::Pathing::DFS;
 use meshed::graph::{Graph, GraphDefinition, Inverted};
+use serde::Serialize;
 use thiserror::Error;
-use webpack_stats::chunk::{Chunk, ChunkId, Chunks, Files};
+use webpack_stats::asset::Asset;
+use webpack_stats::chunk::{Chunk, ChunkId, ChunkModules, Chunks, Files};
+use webpack_stats::diagnostic::Diagnostic;
 use webpack_stats::entry::Entrypoint;
-use webpack_stats::module::{Module, ModuleIdentifier, ModuleName, Modules};
+use webpack_stats::import::{ImportLocation, ImportType};
+use webpack_stats::module::{
+    IncludedModuleNames, Module, ModuleChunks, ModuleIdentifier, ModuleName, Modules,
+};
 use webpack_stats::SizeBytes;
 
 #[derive(Debug, Error)]
@@ -39,9 +49,9 @@ pub enum EntrypointTraversalError {
     NoEntrypoint { id: String },
     #[error("Entrypoint {id} contains invalid chunks: {chunks:?}. Expected chunk {expected}")]
     InvalidEntrypointChunks {
-        chunks: HashSet<ChunkId>,
+        chunks: Vec<String>,
         id: String,
-        expected: ChunkId,
+        expected: String,
     },
 
     #[error("An unexpected error occured when traversing the graph")]
@@ -49,13 +59,11 @@ pub enum EntrypointTraversalError {
 }
 
 pub fn find_possible_chunk_for(
-    node: &Node<ModuleParentGraph>,
+    chunks: &ModuleChunks,
     origin_chunk_id: ChunkId,
     origin_chunk: Option<Node<ChunkGraph>>,
     import_paths: &Graph<ChunkImportPathGraph>,
 ) -> Option<ChunkId> {
-    let chunks = node.node_data();
-
     // Included in current chunk
     if chunks.is_empty() || chunks.contains(&origin_chunk_id) {
         return Some(origin_chunk_id);
@@ -97,60 +105,109 @@ pub fn find_possible_chunk_for(
     traversal.flatten().next()
 }
 
-fn annotate_with_chunk<T: GraphDefinition>(
-    node: &Node<T>,
-    chunk: Option<ChunkId>,
-    fallback: ChunkId,
-) {
-    #[derive(Clone)]
-    struct Defaulted;
+/// The chunk every module of a traversal was assigned to. Kept next to the
+/// traversal instead of on the nodes so the module graph can be shared
+#[derive(Default)]
+pub struct ChunkAssignments {
+    chunks: HashMap<ModuleIdentifier, ChunkId>,
+    /// Modules that were given the chunk they were imported from because no
+    /// better chunk was found
+    defaulted: HashSet<ModuleIdentifier>,
+}
+
+impl ChunkAssignments {
+    pub fn get(&self, module: &ModuleIdentifier) -> Option<&ChunkId> {
+        self.chunks.get(module)
+    }
+
+    fn insert(&mut self, module: ModuleIdentifier, chunk: ChunkId) {
+        self.chunks.insert(module, chunk);
+    }
 
-    if let Some(chunk) = chunk {
-        tracing::trace!("Annotate {}", &chunk);
-        if let Some(existing_annotation) = node.get_annotation::<ChunkId>() {
-            if existing_annotation != chunk && node.get_annotation::<Defaulted>().is_none() {
-                tracing::warn!("Subsequent traversal of {} resulted in inconsistent chunk assignment {} -> {}. Module belongs to multiple chunks in traversal", node.label(), &existing_annotation, &chunk);
+    fn assign(
+        &mut self,
+        node: &ArenaNode<ModuleParentGraph>,
+        chunk: Option<ChunkId>,
+        fallback: ChunkId,
+    ) {
+        let module = node.get_id();
+        if let Some(chunk) = chunk {
+            tracing::trace!("Annotate {}", &chunk);
+            if let Some(existing_annotation) = self.chunks.get(&module) {
+                if existing_annotation != &chunk && !self.defaulted.contains(&module) {
+                    tracing::warn!("Subsequent traversal of {} resulted in inconsistent chunk assignment {} -> {}. Module belongs to multiple chunks in traversal", node.label(), existing_annotation, &chunk);
+                }
+            }
+            self.chunks.insert(module, chunk);
+        } else if !self.chunks.contains_key(&module) {
+            self.defaulted.insert(module.clone());
+            self.chunks.insert(module, fallback);
+        }
+    }
+
+    /// Annotate the nodes of the traversed graph with their chunk
+    fn annotate(&self, graph: &Inverted<ModuleParentGraph>) {
+        for node in graph.inner().all_nodes() {
+            if let Some(chunk) = self.chunks.get(&node.get_id()) {
+                node.annotate(chunk.clone());
             }
         }
-        node.annotate(chunk);
-    } else if node.get_annotation::<ChunkId>().is_some() {
-    } else {
-        node.annotate(Defaulted);
-        node.annotate(fallback);
     }
 }
 
+/// Copy the traversed part of `modules` out of the arena. `traversal` walked
+/// the inverted arena, so its edges are flipped back before extracting.
+fn extract_traversal(
+    modules: &ArenaGraph<ModuleParentGraph>,
+    traversal: GraphTraversal<ModuleParentGraph>,
+    chunks: &ChunkAssignments,
+) -> Inverted<ModuleParentGraph> {
+    let parents = TraversalLog {
+        nodes: traversal.nodes,
+        edges: traversal
+            .edges
+            .into_iter()
+            .map(|(origin, target)| (target, origin))
+            .collect(),
+    };
+    let graph = parents.extract_from_arena(modules).invert();
+    chunks.annotate(&graph);
+    graph
+}
+
 pub fn traverse_entrypoint(
     entrypoint_id: ModuleIdentifier,
     initial_chunk_id: ChunkId,
-    module_graph: &Inverted<ModuleParentGraph>,
+    module_graph: &ArenaGraph<ModuleParentGraph>,
     truncated_chunk_graph: &Graph<ChunkGraph>,
     import_paths: &Graph<ChunkImportPathGraph>,
+    chunks: &mut ChunkAssignments,
 ) -> Result<GraphTraversal<ModuleParentGraph>, EntrypointTraversalError> {
-    let entrypoint = module_graph.inner().query(&entrypoint_id).ok_or(
-        EntrypointTraversalError::NoEntrypoint {
-            id: entrypoint_id.to_string(),
-        },
-    )?;
+    let entrypoint =
+        module_graph
+            .node(&entrypoint_id)
+            .ok_or(EntrypointTraversalError::NoEntrypoint {
+                id: entrypoint_id.to_string(),
+            })?;
 
     {
         let module_chunks = entrypoint.node_data();
         if module_chunks.is_empty() {
-            entrypoint.annotate(initial_chunk_id);
+            chunks.insert(entrypoint_id, initial_chunk_id);
         } else if module_chunks.len() == 1 {
-            entrypoint.annotate(module_chunks.iter().cloned().next().unwrap());
+            chunks.insert(entrypoint_id, module_chunks.iter().next().cloned().unwrap());
         } else if !module_chunks.contains(&initial_chunk_id) {
             return Err(EntrypointTraversalError::InvalidEntrypointChunks {
-                chunks: module_chunks.clone(),
+                chunks: module_chunks.iter().map(ToString::to_string).collect(),
                 id: entrypoint_id.to_string(),
-                expected: initial_chunk_id.clone(),
+                expected: initial_chunk_id.to_string(),
             });
         } else {
-            entrypoint.annotate(initial_chunk_id);
+            chunks.insert(entrypoint_id, initial_chunk_id);
         }
     }
 
-    let traversal = traverse_graph(entrypoint.clone())
+    let traversal = traverse_graph(entrypoint)
         .set_pathing(Pathing::DFS)
         .set_mode(Mode::Acyclic)
         .execute(|_depth, edge| {
@@ -159,29 +216,218 @@ pub fn traverse_entrypoint(
                 edge.origin.label(),
                 edge.target.label()
             );
-            let origin_chunk = edge
-                .origin
-                .get_annotation::<ChunkId>()
+            let origin_chunk = chunks
+                .get(&edge.origin.get_id())
+                .cloned()
                 .expect("Traversal did not have a source chunk");
 
-            let target_node = &edge.target;
-
             // find all the outgoing edges in the chunk graph (subtraversal)
             let origin_chunk_node = truncated_chunk_graph.query(&origin_chunk).cloned();
 
             let chunk = find_possible_chunk_for(
-                target_node,
+                edge.target.node_data(),
                 origin_chunk.clone(),
                 origin_chunk_node,
                 import_paths,
             );
-            annotate_with_chunk(&edge.target, chunk, origin_chunk);
+            chunks.assign(&edge.target, chunk, origin_chunk);
             Instruction::Continue(())
         });
 
     Ok(traversal)
 }
 
+/// The module and chunk graphs of a compilation. Building them once lets a
+/// long lived session answer many queries. The module graph is stored in an
+/// [`ArenaGraph`] and inverted once. Traversals keep the chunk of every module
+/// in [`ChunkAssignments`] instead of annotating the nodes, so the graphs can
+/// be shared between traversals and threads with the `parallel` feature
+pub struct CompilationGraphs {
+    pub modules: ArenaGraph<ModuleParentGraph>,
+    /// [`Self::modules`] inverted, every module points to the modules it imports
+    pub imports: ArenaGraph<ModuleParentGraph>,
+    pub chunks: Graph<ChunkGraph>,
+    pub import_paths: Graph<ChunkImportPathGraph>,
+    pub chunk_loads: Graph<ChunkLoadGraph>,
+}
+
+impl CompilationGraphs {
+    pub fn build<M, Mv, C, Cv>(modules: &M, chunks: &C) -> Self
+    where
+        M: Modules<Mv>,
+        Mv: Module,
+        C: Chunks<Cv>,
+        Cv: Chunk,
+    {
+        let module_arena = ModuleParentGraph::build_arena(modules);
+        Self {
+            imports: module_arena.invert(),
+            modules: module_arena,
+            chunks: ChunkGraph::build_graph(chunks),
+            import_paths: ChunkImportPathGraph::build_graph(chunks),
+            chunk_loads: ChunkLoadGraph::build_graph(chunks),
+        }
+    }
+
+    /// Every module loaded from the entry chunks, see [`traverse_entry_chunk`]
+    pub fn traverse_entrypoint(
+        &self,
+        entry_chunks: &[ChunkId],
+    ) -> Result<Inverted<ModuleParentGraph>, EntrypointTraversalError> {
+        let mut traversal: Option<GraphTraversal<ModuleParentGraph>> = None;
+        let mut assignments = ChunkAssignments::default();
+        let chunk_graph = &self.chunks;
+        let valid_import_graph = &self.import_paths;
+
+        for entrypoint_id in entry_chunks.iter() {
+            let chunk = chunk_graph
+                .query(entrypoint_id)
+                .cloned()
+                .ok_or(EntrypointTraversalError::GraphError)?;
+
+            let chunk_traversal = {
+                traverse_graph(chunk.clone())
+                    .set_mode(Acyclic)
+                    .execute(|_depth, _edge| Instruction::Continue(()))
+            };
+
+            let truncated_chunk_graph = chunk_traversal.project_into_graph(chunk_graph);
+            let import_paths = chunk_traversal.prune_graph(valid_import_graph);
+
+            let entrypoints = chunk.node_data();
+
+            for entrypoint in entrypoints {
+                let traversal_log = traverse_entrypoint(
+                    entrypoint.clone(),
+                    entrypoint_id.clone(),
+                    &self.imports,
+                    &truncated_chunk_graph,
+                    &import_paths,
+                    &mut assignments,
+                );
+
+                match traversal_log {
+                    Ok(traversal_log) => {
+                        if let Some(old_traversal) = traversal.take() {
+                            let next = old_traversal.merge_with(traversal_log);
+                            traversal = Some(next);
+                        } else {
+                            traversal = Some(traversal_log)
+                        }
+                    }
+                    Err(err) => {
+                        tracing::warn!("{}. Skipping", err);
+                    }
+                }
+            }
+        }
+
+        let traversal = traversal.ok_or(EntrypointTraversalError::GraphError)?;
+        Ok(extract_traversal(&self.modules, traversal, &assignments))
+    }
+
+    /// Every module loaded from each of the entrypoints, see
+    /// [`traverse_all_entrypoints`]
+    pub fn traverse_all_entrypoints<'e, E>(
+        &self,
+        entrypoints: &[&'e E],
+    ) -> Vec<(
+        &'e str,
+        Result<Inverted<ModuleParentGraph>, EntrypointTraversalError>,
+    )>
+    where
+        E: SharedEntrypoint,
+    {
+        #[cfg(feature = "parallel")]
+        use rayon::prelude::*;
+
+        #[cfg(feature = "parallel")]
+        let entrypoints = entrypoints.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let entrypoints = entrypoints.iter();
+
+        entrypoints
+            .map(|entrypoint| {
+                let graph = self.traverse_entrypoint(entrypoint.chunks());
+                (entrypoint.name(), graph)
+            })
+            .collect()
+    }
+
+    /// Every chunk the entry chunks load, see [`traverse_entry_chunks`]
+    pub fn load_entry_chunks(&self, entry_chunks: &[ChunkId]) -> Graph<ChunkLoadGraph> {
+        load_entry_chunks(&self.chunk_loads, entry_chunks)
+    }
+
+    /// The import paths from the entry chunks to `target_chunk`, see
+    /// [`paths_to_chunk`]
+    pub fn paths_to_chunk(
+        &self,
+        entry_chunks: &[ChunkId],
+        target_chunk: ChunkId,
+    ) -> Inverted<ModuleParentGraph> {
+        let chunk_graph = &self.chunks;
+        let import_chunk_graph = &self.import_paths;
+        let mut assignments = ChunkAssignments::default();
+        // traverse every chunk entrypoint until we hit  the target chunk. Store the paths.
+        let mut paths = vec![] as Vec<Vec<(ModuleIdentifier, ModuleIdentifier)>>;
+        for root_chunk in entry_chunks {
+            if root_chunk == &target_chunk {
+                continue;
+            }
+            let chunk_node = chunk_graph.query(root_chunk).unwrap().clone();
+            // each module
+            for module in chunk_node.node_data() {
+                let module_node = self.imports.node(module).unwrap();
+                assignments.insert(module.clone(), chunk_node.get_id());
+
+                let traversal = traverse_graph(module_node)
+                    .set_mode(Acyclic)
+                    .set_pathing(DFS);
+
+                traversal.execute(|meta, edge| {
+                    let origin_chunk = assignments
+                        .get(&edge.origin.get_id())
+                        .cloned()
+                        .expect("Did not have an origin chunk");
+                    let origin_chunk_node = chunk_graph.query(&origin_chunk).cloned();
+                    let mut path = meta
+                        .get_annotation::<Vec<(ModuleIdentifier, ModuleIdentifier)>>()
+                        .unwrap_or_default();
+                    path.push((edge.origin.get_id(), edge.target.get_id()));
+
+                    let node_chunk = find_possible_chunk_for(
+                        edge.target.node_data(),
+                        origin_chunk.clone(),
+                        origin_chunk_node,
+                        import_chunk_graph,
+                    );
+
+                    assignments.assign(&edge.target, node_chunk.clone(), origin_chunk);
+
+                    // check if we've arrived at the target chunk
+                    if let Some(chunk) = node_chunk {
+                        if chunk == target_chunk {
+                            paths.push(path);
+                            return Instruction::Backtrack(());
+                        }
+                    }
+                    Instruction::Continue(())
+                });
+            }
+        }
+
+        let mut log = TraversalLog::default();
+        for path in paths {
+            let next_log = TraversalLog::from(path);
+            log = log.merge_with(next_log);
+        }
+
+        // merge into a single traversal
+        extract_traversal(&self.modules, log, &assignments)
+    }
+}
+
 pub fn traverse_entry_chunk<M, C, Mv, Cv, E>(
     modules: M,
     chunks: C,
@@ -194,57 +440,69 @@ where
     Cv: Chunk,
     E: Entrypoint,
 {
-    let chunk_ids = entrypoint.chunks();
-    let mut traversal: Option<GraphTraversal<ModuleParentGraph>> = None;
-    let chunk_graph = ChunkGraph::build_graph(&chunks);
-    let valid_import_graph = ChunkImportPathGraph::build_graph(&chunks);
-
-    let module_graph = ModuleParentGraph::build_graph(&modules).invert();
-
-    for entrypoint_id in chunk_ids.iter().cloned() {
-        let chunk = chunk_graph
-            .query(&entrypoint_id)
-            .cloned()
-            .ok_or(EntrypointTraversalError::GraphError)?;
-
-        let chunk_traversal = {
-            traverse_graph(chunk.clone())
-                .set_mode(Acyclic)
-                .execute(|_depth, _edge| Instruction::Continue(()))
-        };
-
-        let truncated_chunk_graph = chunk_traversal.project_into_graph(&chunk_graph);
-        let import_paths = chunk_traversal.prune_graph(&valid_import_graph);
-
-        let entrypoints = chunk.node_data();
+    CompilationGraphs::build(&modules, &chunks).traverse_entrypoint(entrypoint.chunks())
+}
 
-        for entrypoint in entrypoints {
-            let traversal_log = traverse_entrypoint(
-                entrypoint.clone(),
-                entrypoint_id,
-                &module_graph,
-                &truncated_chunk_graph,
-                &import_paths,
-            );
+/// Every chunk an entrypoint can load and the chunks that load them. The
+/// chunk level counterpart of [`traverse_entry_chunk`]
+pub fn traverse_entry_chunks<C, Cv, E>(chunks: &C, entrypoint: &E) -> Graph<ChunkLoadGraph>
+where
+    C: Chunks<Cv>,
+    Cv: Chunk,
+    E: Entrypoint,
+{
+    load_entry_chunks(&ChunkLoadGraph::build_graph(chunks), entrypoint.chunks())
+}
 
-            match traversal_log {
-                Ok(traversal_log) => {
-                    if let Some(old_traversal) = traversal.take() {
-                        let next = old_traversal.merge_with(traversal_log);
-                        traversal = Some(next);
-                    } else {
-                        traversal = Some(traversal_log)
-                    }
-                }
-                Err(err) => {
-                    tracing::warn!("{}. Skipping", err);
-                }
-            }
+fn load_entry_chunks(
+    graph: &Graph<ChunkLoadGraph>,
+    entry_chunks: &[ChunkId],
+) -> Graph<ChunkLoadGraph> {
+    let mut log = TraversalLog::default();
+    for chunk in entry_chunks {
+        if let Some(node) = graph.query(chunk) {
+            let traversal = traverse_graph(node.clone())
+                .set_mode(Mode::Acyclic)
+                .execute(|_, _| Instruction::Continue(()));
+            log = log.merge_with(traversal);
+            log.nodes.insert(chunk.clone());
         }
     }
 
-    let traversal = traversal.unwrap();
-    Ok(module_graph.map_project(traversal))
+    log.project_into_graph(graph)
+}
+
+/// Entrypoints that [`traverse_all_entrypoints`] accepts. With the `parallel`
+/// feature they are shared between threads.
+#[cfg(not(feature = "parallel"))]
+pub trait SharedEntrypoint: Entrypoint {}
+#[cfg(not(feature = "parallel"))]
+impl<E: Entrypoint> SharedEntrypoint for E {}
+
+#[cfg(feature = "parallel")]
+pub trait SharedEntrypoint: Entrypoint + Sync {}
+#[cfg(feature = "parallel")]
+impl<E: Entrypoint + Sync> SharedEntrypoint for E {}
+
+/// Traverse every entrypoint of a compilation. The graphs are built once and
+/// shared, the entrypoints are traversed in parallel with the `parallel`
+/// feature and one after the other without it.
+pub fn traverse_all_entrypoints<'e, M, C, Mv, Cv, E>(
+    modules: &M,
+    chunks: &C,
+    entrypoints: &[&'e E],
+) -> Vec<(
+    &'e str,
+    Result<Inverted<ModuleParentGraph>, EntrypointTraversalError>,
+)>
+where
+    M: Modules<Mv>,
+    Mv: Module,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+    E: SharedEntrypoint,
+{
+    CompilationGraphs::build(modules, chunks).traverse_all_entrypoints(entrypoints)
 }
 
 pub struct Entrypoints<'a> {
@@ -289,71 +547,218 @@ where
     Cv: Chunk,
     E: Entrypoint,
 {
-    let chunk_graph = ChunkGraph::build_graph(&chunks);
-    let import_chunk_graph = ChunkImportPathGraph::build_graph(&chunks);
-    let module_graph = ModuleParentGraph::build_graph(&modules).invert();
-    // traverse every chunk entrypoint until we hit  the target chunk. Store the paths.
-    let mut paths = vec![] as Vec<Vec<(ModuleIdentifier, ModuleIdentifier)>>;
-    for root_chunk in entrypoint.chunks() {
-        if root_chunk == &target_chunk {
-            continue;
+    CompilationGraphs::build(modules, chunks).paths_to_chunk(entrypoint.chunks(), target_chunk)
+}
+
+/// A single import of a chain, from the importing module to the imported one
+#[derive(Debug, Clone)]
+pub struct ImportHop {
+    pub from: ModuleName,
+    pub to: ModuleName,
+    pub import_type: ImportType,
+    /// Location of the import in `from`
+    pub loc: ImportLocation,
+}
+
+/// Shortest chain of imports from an entry module to `target`. Empty when
+/// `target` is an entry module
+#[derive(Debug, Clone)]
+pub struct ImportChain {
+    pub target: ModuleName,
+    /// The module `target` was concatenated into, which is the module that
+    /// is actually imported
+    pub concatenated_into: Option<ModuleName>,
+    pub hops: Vec<ImportHop>,
+}
+
+pub struct ModuleInclusion {
+    pub entrypoint: String,
+    pub pattern: String,
+    pub chains: Vec<ImportChain>,
+    /// Modules that match the pattern but are not loaded by the entrypoint
+    pub unreachable: Vec<ModuleName>,
+    /// Every module and import of [`Self::chains`]
+    pub graph: Inverted<ModuleParentGraph>,
+}
+
+impl Display for ModuleInclusion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.chains.is_empty() && self.unreachable.is_empty() {
+            return writeln!(f, "No module matches {}", &self.pattern);
         }
-        let chunk_node = chunk_graph.query(root_chunk).unwrap().clone();
-        // each module
-        for module in chunk_node.node_data() {
-            let module_node = module_graph.inner().query(module).unwrap().clone();
-            module_node.annotate(chunk_node.get_id());
-
-            let traversal = traverse_graph(module_node)
-                .set_mode(Acyclic)
-                .set_pathing(DFS);
-
-            traversal.execute(|meta, edge| {
-                let origin_chunk = edge
-                    .origin
-                    .get_annotation::<ChunkId>()
-                    .expect("Did not have an origin chunk");
-                let origin_chunk_node = chunk_graph.query(&&origin_chunk).cloned();
-                let mut path = meta
-                    .get_annotation::<Vec<(ModuleIdentifier, ModuleIdentifier)>>()
-                    .unwrap_or_default();
-                path.push((edge.origin.get_id(), edge.target.get_id()));
-
-                let node_chunk = find_possible_chunk_for(
-                    &edge.target,
-                    origin_chunk.clone(),
-                    origin_chunk_node,
-                    &import_chunk_graph,
-                );
 
-                annotate_with_chunk(&edge.target, node_chunk.clone(), origin_chunk);
+        for chain in self.chains.iter() {
+            write!(f, "{}", &chain.target)?;
+            if let Some(module) = &chain.concatenated_into {
+                write!(f, " (concatenated into {})", module)?;
+            }
+            writeln!(f, " is included by {}:", &self.entrypoint)?;
 
-                // check if we've arrived at the target chunk
-                if let Some(chunk) = node_chunk {
-                    if chunk == target_chunk {
-                        paths.push(path);
-                        return Instruction::Backtrack(());
-                    }
+            let imported = chain.concatenated_into.as_ref().unwrap_or(&chain.target);
+            match chain.hops.first() {
+                None => writeln!(f, "  {} (entry module)", imported)?,
+                Some(first) => writeln!(f, "  {}", &first.from)?,
+            }
+            for (depth, hop) in chain.hops.iter().enumerate() {
+                let indent = depth * 4 + 6;
+                write!(f, "{:>indent$}{} ({:?}", "└── ", &hop.to, &hop.import_type)?;
+                if !hop.loc.0.is_empty() {
+                    write!(f, " at {}", &hop.loc)?;
                 }
-                edge.target.annotate(path);
-                Instruction::Continue(())
-            });
+                writeln!(f, ")")?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.unreachable.is_empty() {
+            writeln!(f, "Not loaded by {}:", &self.entrypoint)?;
+            for module in self.unreachable.iter() {
+                writeln!(f, "  {}", module)?;
+            }
         }
+
+        Ok(())
     }
+}
 
+/// Explain why modules are loaded by an entrypoint. Finds the shortest
+/// import chain from the entry modules of `entrypoint` to every module whose
+/// name or identifier contains `pattern`. Modules concatenated into another
+/// module are reported through the module they were concatenated into.
+pub fn why_module<E, C, Cv, M, Mv>(
+    entrypoint: &E,
+    pattern: &str,
+    chunks: &C,
+    modules: &M,
+) -> ModuleInclusion
+where
+    M: Modules<Mv>,
+    Mv: Module,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+    E: Entrypoint,
+{
+    let module_graph = ModuleParentGraph::build_graph(&modules).invert();
+
+    // Entry modules are the modules of the entrypoint chunks that nothing
+    // imports
+    let imported: HashSet<ModuleIdentifier> = module_graph
+        .inner()
+        .all_edges()
+        .map(|edge| edge.target.get_id())
+        .collect();
+    let chunk_modules: Vec<ModuleIdentifier> = entrypoint
+        .chunks()
+        .iter()
+        .filter_map(|chunk| chunks.query(chunk))
+        .flat_map(|chunk| ExtractData::<ChunkModules>::extract_data(chunk))
+        .collect();
+    let mut roots: Vec<_> = chunk_modules
+        .iter()
+        .filter(|module| !imported.contains(*module))
+        .cloned()
+        .collect();
+    if roots.is_empty() {
+        roots = chunk_modules;
+    }
+
+    // Breadth first, so the first edge that reaches a module is on a
+    // shortest chain
+    let mut reached_by: HashMap<ModuleIdentifier, Option<Edge<ModuleParentGraph>>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for root in roots {
+        if let Some(node) = module_graph.inner().query(&root) {
+            if reached_by.insert(root, None).is_none() {
+                queue.push_back(node.clone());
+            }
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        node.for_each_edge(|edge| {
+            if let Entry::Vacant(entry) = reached_by.entry(edge.target.get_id()) {
+                queue.push_back(edge.target.clone());
+                entry.insert(Some(edge));
+            }
+        });
+    }
+
+    // Match module names first, including the names of concatenated
+    // modules. Identifiers are full paths and match too much, so they are
+    // only used when no name matches
+    let mut targets: Vec<(ModuleIdentifier, ModuleName, Option<ModuleName>)> = vec![];
+    for module in modules.all() {
+        let names: IncludedModuleNames = module.extract_data();
+        let label = module.label();
+        for name in names.0.into_iter().filter(|name| name.contains(pattern)) {
+            let concatenated_into = (name != label.0.as_ref()).then(|| label.clone());
+            targets.push((module.get_id(), ModuleName(name.into()), concatenated_into));
+        }
+    }
+    if targets.is_empty() {
+        targets = modules
+            .all()
+            .into_iter()
+            .filter(|module| module.get_id().0.contains(pattern))
+            .map(|module| (module.get_id(), module.label(), None))
+            .collect();
+    }
+    // Runtime modules are listed once per chunk that contains them. The same
+    // module always has the same chain, so it is only a target once
+    let mut seen = HashSet::new();
+    targets.retain(|(id, name, _)| seen.insert((id.clone(), name.0.clone())));
+    targets.sort_by(|(_, a, _), (_, b, _)| a.0.cmp(&b.0));
+
+    let mut chains = vec![];
+    let mut unreachable = vec![];
     let mut log = TraversalLog::default();
-    for path in paths {
-        let next_log = TraversalLog::from(path);
-        log = log.merge_with(next_log);
+    for (target, name, concatenated_into) in targets {
+        if !reached_by.contains_key(&target) {
+            if !unreachable
+                .iter()
+                .any(|module: &ModuleName| module.0 == name.0)
+            {
+                unreachable.push(name);
+            }
+            continue;
+        }
+
+        let mut hops = vec![];
+        let mut current = target.clone();
+        while let Some(Some(edge)) = reached_by.get(&current) {
+            let (import_type, _, loc) = edge.meta.as_ref();
+            hops.push(ImportHop {
+                from: edge.origin.label().clone(),
+                to: edge.target.label().clone(),
+                import_type: *import_type,
+                loc: loc.clone(),
+            });
+            log.edges.insert((edge.origin.get_id(), current.clone()));
+            log.nodes.insert(current);
+            current = edge.origin.get_id();
+        }
+        log.nodes.insert(current);
+        hops.reverse();
+
+        chains.push(ImportChain {
+            target: name,
+            concatenated_into,
+            hops,
+        });
     }
 
-    module_graph.map_project(log)
-    // merge into a single traversal
+    ModuleInclusion {
+        entrypoint: entrypoint.name().to_string(),
+        pattern: pattern.to_string(),
+        chains,
+        unreachable,
+        graph: module_graph.map_project(log),
+    }
 }
 
 pub struct EntrypointDescription<'a> {
     name: &'a str,
     initial_load_size: SizeBytes,
+    initial_compressed_size: CompressedSize,
     roots: Vec<Node<ChunkLoadGraph>>,
 }
 
@@ -365,6 +770,11 @@ impl<'a> Display for EntrypointDescription<'a> {
             "Initial size (uncompressed): {}",
             &self.initial_load_size
         )?;
+        writeln!(
+            f,
+            "Initial size (compressed): {}",
+            &self.initial_compressed_size
+        )?;
         writeln!(f, "Chunk Imports (* denotes asynchronous chunk):")?;
         for root in self.roots.iter() {
             let traversal = traverse_graph(root.clone())
@@ -412,11 +822,16 @@ impl<'a> Display for EntrypointDescription<'a> {
 }
 
 #[derive(Debug, Error)]
-pub enum EntrypointDescriptionError {}
+pub enum EntrypointDescriptionError {
+    #[error("Entrypoint {0} does not exist")]
+    NoEntrypoint(String),
+}
+
 pub fn describe_entrypoints<'a, C, Cv>(
     chunks: C,
     entrypoint_name: &'a str,
     entrypoints: Entrypoints,
+    estimator: &CompressionEstimator,
 ) -> Result<EntrypointDescription<'a>, EntrypointDescriptionError>
 where
     C: Chunks<Cv>,
@@ -424,8 +839,10 @@ where
 {
     let graph = ChunkLoadGraph::build_graph(&chunks);
 
-    let entrypoint = *entrypoints.entries.get(entrypoint_name).unwrap();
-    let mut output_traversal = None as Option<GraphTraversal<ChunkLoadGraph>>;
+    let entrypoint = *entrypoints
+        .entries
+        .get(entrypoint_name)
+        .ok_or_else(|| EntrypointDescriptionError::NoEntrypoint(entrypoint_name.to_string()))?;
     let mut root_nodes = vec![];
     for chunk in entrypoint.iter() {
         let chunk_node = graph.query(chunk);
@@ -436,18 +853,6 @@ where
             Some(node) => node.clone(),
         };
 
-        let truncated = traverse_graph(chunk_node.clone())
-            .set_pathing(Pathing::DFS)
-            .set_mode(Mode::Acyclic)
-            .execute(|_, edge| {
-                let initial = &edge.target.node_data().2;
-                if !initial.0 {
-                    Instruction::Skip(())
-                } else {
-                    Instruction::Continue(())
-                }
-            });
-
         let unique_paths = traverse_graph(chunk_node.clone())
             .set_pathing(DFS)
             .set_mode(Mode::Acyclic)
@@ -469,31 +874,83 @@ where
 
         let root_node = projection.query(&chunk_node.get_id()).expect("");
         root_nodes.push(root_node.clone());
-        if let Some(tra) = output_traversal.take() {
-            output_traversal = Some(tra.merge_with(truncated))
-        } else {
-            output_traversal = Some(truncated)
-        }
     }
 
-    let output_traversal = output_traversal.unwrap();
-    let output_graph = output_traversal.project_into_graph(&graph);
-
-    let initial_size = output_graph
-        .all_nodes()
-        .fold(SizeBytes::default(), |acc, n| acc + n.node_data().1);
+    let initial_compressed_size = initial_chunks(&graph, entrypoint)
+        .iter()
+        .filter_map(|chunk| chunks.query(chunk))
+        .map(|chunk| estimator.chunk(chunk))
+        .reduce(|acc, size| acc + size)
+        .unwrap_or_default();
 
     Ok(EntrypointDescription {
         name: entrypoint_name,
         roots: root_nodes,
-        initial_load_size: initial_size,
+        initial_load_size: initial_load_size(&graph, entrypoint),
+        initial_compressed_size,
     })
 }
 
-#[derive(Debug)]
+/// Every chunk that is loaded synchronously when an entrypoint made of
+/// `entry_chunks` is loaded
+pub fn initial_chunks(graph: &Graph<ChunkLoadGraph>, entry_chunks: &[ChunkId]) -> HashSet<ChunkId> {
+    let mut initial_chunks = HashSet::new();
+    for chunk in entry_chunks {
+        let chunk_node = match graph.query(chunk) {
+            None => continue,
+            Some(node) => node.clone(),
+        };
+
+        let truncated = traverse_graph(chunk_node)
+            .set_pathing(Pathing::DFS)
+            .set_mode(Mode::Acyclic)
+            .execute(|_, edge| {
+                let initial = &edge.target.node_data().2;
+                if !initial.0 {
+                    Instruction::Skip(())
+                } else {
+                    Instruction::Continue(())
+                }
+            });
+        initial_chunks.extend(truncated.nodes);
+    }
+
+    initial_chunks
+}
+
+/// Every chunk that can be loaded, synchronously or not, once an entrypoint
+/// made of `entry_chunks` is loaded
+pub fn reachable_chunks(
+    graph: &Graph<ChunkLoadGraph>,
+    entry_chunks: &[ChunkId],
+) -> HashSet<ChunkId> {
+    let mut reachable_chunks = HashSet::new();
+    for chunk in entry_chunks {
+        if let Some(node) = graph.query(chunk) {
+            let reachable = traverse_graph(node.clone())
+                .set_mode(Mode::Acyclic)
+                .execute(|_, _| Instruction::Continue(()));
+            reachable_chunks.extend(reachable.nodes);
+        }
+    }
+
+    reachable_chunks
+}
+
+/// Size of every chunk that is loaded synchronously when an entrypoint
+/// made of `entry_chunks` is loaded
+pub fn initial_load_size(graph: &Graph<ChunkLoadGraph>, entry_chunks: &[ChunkId]) -> SizeBytes {
+    initial_chunks(graph, entry_chunks)
+        .iter()
+        .filter_map(|chunk| graph.query(chunk))
+        .fold(SizeBytes::default(), |acc, n| acc + n.node_data().1)
+}
+
+#[derive(Debug, Serialize)]
 pub struct ChunkDescription {
     id: ChunkId,
     size: SizeBytes,
+    compressed_size: CompressedSize,
     files: Files,
     modules: Vec<ModuleName>,
 }
@@ -502,6 +959,7 @@ impl Display for ChunkDescription {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Chunk: {}", &self.id)?;
         writeln!(f, "size: {}", &self.size)?;
+        writeln!(f, "compressed size: {}", &self.compressed_size)?;
         writeln!(f, "Files:")?;
         for file in self.files.0.iter() {
             writeln!(f, "  {}", &file)?;
@@ -520,6 +978,7 @@ pub fn describe_chunk<C: Chunks<Cv>, Cv: Chunk, M: Modules<Mv>, Mv: Module>(
     chunk_id: ChunkId,
     chunks: &C,
     modules: &M,
+    estimator: &CompressionEstimator,
 ) -> Option<ChunkDescription> {
     let node = chunks.query(&chunk_id)?;
 
@@ -532,7 +991,483 @@ pub fn describe_chunk<C: Chunks<Cv>, Cv: Chunk, M: Modules<Mv>, Mv: Module>(
     Some(ChunkDescription {
         id: chunk_id,
         size: node.extract_data(),
+        compressed_size: estimator.chunk(node),
         files: node.extract_data(),
         modules: names.collect(),
     })
 }
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+const NO_MODULE: &str = "(no module)";
+const NO_ENTRYPOINT: &str = "(no entrypoint)";
+
+pub struct DiagnosticsReport<'a> {
+    diagnostics: Vec<(Severity, &'a Diagnostic<'a>)>,
+    /// Module name -> index into diagnostics
+    by_module: BTreeMap<String, Vec<usize>>,
+    /// Entrypoint name -> index into diagnostics
+    by_entrypoint: BTreeMap<String, Vec<usize>>,
+}
+
+impl<'a> DiagnosticsReport<'a> {
+    pub fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|(s, _)| *s == severity)
+            .count()
+    }
+
+    /// Diagnostics whose kind is exactly `pattern` or whose message contains it
+    pub fn matching<'p>(
+        &'p self,
+        pattern: &'p str,
+    ) -> impl Iterator<Item = (Severity, &'a Diagnostic<'a>)> + 'p {
+        self.diagnostics
+            .iter()
+            .copied()
+            .filter(move |(_, diagnostic)| {
+                diagnostic.kind() == Some(pattern) || diagnostic.message.contains(pattern)
+            })
+    }
+}
+
+impl<'a> Display for DiagnosticsReport<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} errors, {} warnings",
+            self.count(Severity::Error),
+            self.count(Severity::Warning)
+        )?;
+
+        for (title, groups) in [
+            ("By module:", &self.by_module),
+            ("By entrypoint:", &self.by_entrypoint),
+        ] {
+            if groups.is_empty() {
+                continue;
+            }
+            writeln!(f)?;
+            writeln!(f, "{}", title)?;
+            for (name, indices) in groups.iter() {
+                writeln!(f, "  {}", name)?;
+                for index in indices {
+                    let (severity, diagnostic) = self.diagnostics[*index];
+                    writeln!(f, "    {} {}", severity, diagnostic)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Attribute every error and warning to the module that caused it and to the
+/// entrypoints that (eventually) load that module.
+pub fn describe_diagnostics<'a, E, C, Cv, M, Mv>(
+    errors: &'a [Diagnostic<'a>],
+    warnings: &'a [Diagnostic<'a>],
+    entrypoints: &[&E],
+    chunks: &C,
+    modules: &M,
+) -> DiagnosticsReport<'a>
+where
+    E: Entrypoint,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+    M: Modules<Mv>,
+    Mv: Module,
+{
+    let chunk_graph = ChunkGraph::build_graph(chunks);
+    let module_index = modules.create_index();
+
+    // Every chunk an entrypoint can load, including async chunks
+    let entrypoint_chunks = entrypoints
+        .iter()
+        .map(|entrypoint| {
+            let mut loaded: HashSet<ChunkId> = entrypoint.chunks().iter().cloned().collect();
+            for chunk in entrypoint.chunks() {
+                if let Some(node) = chunk_graph.query(chunk) {
+                    traverse_graph(node.clone())
+                        .set_mode(Acyclic)
+                        .into_iterator(|_, edge| Instruction::Continue(edge.target.get_id()))
+                        .for_each(|id| {
+                            loaded.insert(id);
+                        });
+                }
+            }
+            (entrypoint.name(), loaded)
+        })
+        .collect::<Vec<_>>();
+
+    let diagnostics = errors
+        .iter()
+        .map(|diagnostic| (Severity::Error, diagnostic))
+        .chain(
+            warnings
+                .iter()
+                .map(|diagnostic| (Severity::Warning, diagnostic)),
+        )
+        .collect::<Vec<_>>();
+
+    let mut by_module: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let mut by_entrypoint: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+    for (index, (_, diagnostic)) in diagnostics.iter().enumerate() {
+        let module = diagnostic
+            .module_identifier
+            .as_ref()
+            .and_then(|id| module_index.query(id));
+
+        let module_name = match (module, &diagnostic.module_name) {
+            (Some(module), _) => module.label().to_string(),
+            (None, Some(name)) => name.to_string(),
+            (None, None) => NO_MODULE.to_string(),
+        };
+        by_module.entry(module_name).or_default().push(index);
+
+        let diagnostic_chunks: ModuleChunks = match (&diagnostic.chunk_id, module) {
+            (Some(chunk), _) => HashSet::from([chunk.clone()]),
+            (None, Some(module)) => module.extract_data(),
+            // Asset level diagnostics, e.g. size limits
+            (None, None) => match &diagnostic.file {
+                Some(file) => chunks
+                    .all()
+                    .into_iter()
+                    .filter(|chunk| {
+                        let files: Files = (*chunk).extract_data();
+                        files.0.iter().any(|f| f == file)
+                    })
+                    .map(|chunk| chunk.get_id())
+                    .collect(),
+                None => HashSet::new(),
+            },
+        };
+
+        let mut attributed = false;
+        for (name, loaded) in entrypoint_chunks.iter() {
+            if !loaded.is_disjoint(&diagnostic_chunks) {
+                by_entrypoint
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(index);
+                attributed = true;
+            }
+        }
+        if !attributed {
+            by_entrypoint
+                .entry(NO_ENTRYPOINT.to_string())
+                .or_default()
+                .push(index);
+        }
+    }
+
+    DiagnosticsReport {
+        diagnostics,
+        by_module,
+        by_entrypoint,
+    }
+}
+
+/// The sizes of a build that [`diff_stats`] compares. Builds of different
+/// webpack versions can be compared with each other.
+#[derive(Debug, Default)]
+pub struct BuildSizes {
+    pub chunks: BTreeMap<ChunkId, SizeBytes>,
+    /// Modules concatenated into another module are part of its size. Runtime
+    /// modules are summed over the chunks they are in
+    pub modules: BTreeMap<ModuleIdentifier, SizeBytes>,
+    pub assets: BTreeMap<String, SizeBytes>,
+    /// Initial load size of each entrypoint
+    pub entrypoints: BTreeMap<String, SizeBytes>,
+}
+
+impl BuildSizes {
+    pub fn new<E, A, C, Cv, M, Mv>(
+        entrypoints: &[&E],
+        assets: &[A],
+        chunks: &C,
+        modules: &M,
+    ) -> Self
+    where
+        E: Entrypoint,
+        A: Asset,
+        C: Chunks<Cv>,
+        Cv: Chunk,
+        M: Modules<Mv>,
+        Mv: Module,
+    {
+        let graph = ChunkLoadGraph::build_graph(chunks);
+
+        Self {
+            chunks: chunks
+                .all()
+                .into_iter()
+                .map(|chunk| {
+                    (
+                        chunk.get_id(),
+                        ExtractData::<SizeBytes>::extract_data(chunk),
+                    )
+                })
+                .collect(),
+            modules: modules
+                .top_level()
+                .into_iter()
+                .fold(BTreeMap::new(), |mut sizes, module| {
+                    let size = sizes.entry(module.get_id()).or_default();
+                    *size = *size + ExtractData::<SizeBytes>::extract_data(module);
+                    sizes
+                }),
+            assets: assets
+                .iter()
+                .map(|asset| (asset.name().to_string(), asset.size()))
+                .collect(),
+            entrypoints: entrypoints
+                .iter()
+                .map(|entry| {
+                    let size = initial_load_size(&graph, entry.chunks());
+                    (entry.name().to_string(), size)
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SizeChange {
+    Added(SizeBytes),
+    Removed(SizeBytes),
+    Resized { old: SizeBytes, new: SizeBytes },
+}
+
+impl SizeChange {
+    /// Bytes gained (positive) or lost (negative) between the two builds
+    pub fn delta(&self) -> f32 {
+        match self {
+            SizeChange::Added(size) => size.0,
+            SizeChange::Removed(size) => -size.0,
+            SizeChange::Resized { old, new } => new.0 - old.0,
+        }
+    }
+}
+
+impl Display for SizeChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SizeChange::Added(size) => write!(f, "added ({})", size),
+            SizeChange::Removed(size) => write!(f, "removed ({})", size),
+            SizeChange::Resized { old, new } => {
+                let sign = if self.delta() < 0.0 { '-' } else { '+' };
+                let delta = SizeBytes(self.delta().abs());
+                write!(f, "{} -> {} ({}{})", old, new, sign, delta)
+            }
+        }
+    }
+}
+
+/// Everything that was added, removed or resized between two builds
+#[derive(Debug, Default)]
+pub struct StatsDiff {
+    pub chunks: BTreeMap<ChunkId, SizeChange>,
+    pub modules: BTreeMap<ModuleIdentifier, SizeChange>,
+    pub assets: BTreeMap<String, SizeChange>,
+    /// Change of the initial load size of each entrypoint
+    pub entrypoints: BTreeMap<String, SizeChange>,
+}
+
+impl StatsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+            && self.modules.is_empty()
+            && self.assets.is_empty()
+            && self.entrypoints.is_empty()
+    }
+}
+
+impl Display for StatsDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn write_changes<K: Display>(
+            f: &mut Formatter<'_>,
+            title: &str,
+            changes: &BTreeMap<K, SizeChange>,
+        ) -> std::fmt::Result {
+            if changes.is_empty() {
+                return Ok(());
+            }
+            writeln!(f, "{}:", title)?;
+            for (key, change) in changes {
+                writeln!(f, "  {}: {}", key, change)?;
+            }
+            Ok(())
+        }
+
+        if self.is_empty() {
+            return writeln!(f, "No changes");
+        }
+        write_changes(f, "Entrypoints (initial load size)", &self.entrypoints)?;
+        write_changes(f, "Assets", &self.assets)?;
+        write_changes(f, "Chunks", &self.chunks)?;
+        write_changes(f, "Modules", &self.modules)
+    }
+}
+
+fn diff_sizes<K: Ord + Clone>(
+    old: &BTreeMap<K, SizeBytes>,
+    new: &BTreeMap<K, SizeBytes>,
+) -> BTreeMap<K, SizeChange> {
+    let mut changes = BTreeMap::new();
+    for (key, old_size) in old {
+        match new.get(key) {
+            None => {
+                changes.insert(key.clone(), SizeChange::Removed(*old_size));
+            }
+            Some(new_size) if new_size != old_size => {
+                let change = SizeChange::Resized {
+                    old: *old_size,
+                    new: *new_size,
+                };
+                changes.insert(key.clone(), change);
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, new_size) in new {
+        if !old.contains_key(key) {
+            changes.insert(key.clone(), SizeChange::Added(*new_size));
+        }
+    }
+    changes
+}
+
+/// Compare two builds. Chunks are matched by id, modules by identifier and
+/// assets and entrypoints by name. Chunk ids are only stable between builds
+/// that use deterministic ids (the webpack 5 production default).
+pub fn diff_stats(old: &BuildSizes, new: &BuildSizes) -> StatsDiff {
+    StatsDiff {
+        chunks: diff_sizes(&old.chunks, &new.chunks),
+        modules: diff_sizes(&old.modules, &new.modules),
+        assets: diff_sizes(&old.assets, &new.assets),
+        entrypoints: diff_sizes(&old.entrypoints, &new.entrypoints),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webpack_stats::v5::Stats;
+
+    static SOURCE_FILE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../webpack-stats/test_projects/v5/compilation-stats.json"
+    ));
+
+    /// Every node with its chunk and the nodes it imports
+    fn summarize(
+        graph: &Inverted<ModuleParentGraph>,
+    ) -> Vec<(String, Option<ChunkId>, Vec<String>)> {
+        let mut nodes: Vec<_> = graph
+            .inner()
+            .all_nodes()
+            .map(|node: Node<ModuleParentGraph>| {
+                let mut edges = node.with_edges_iter(|edges| {
+                    edges
+                        .map(|edge| edge.target.get_id().to_string())
+                        .collect::<Vec<_>>()
+                });
+                edges.sort();
+                (
+                    node.get_id().to_string(),
+                    node.get_annotation::<ChunkId>(),
+                    edges,
+                )
+            })
+            .collect();
+        nodes.sort();
+        nodes
+    }
+
+    #[test]
+    fn why_module_reports_each_module_once() {
+        let stats: Stats = serde_json::from_str(SOURCE_FILE).unwrap();
+        let entrypoint = stats.entrypoints.get("index").unwrap();
+
+        let inclusion = why_module(entrypoint, "runtime", &stats.chunks, &stats.modules);
+        let targets: Vec<_> = inclusion
+            .chains
+            .iter()
+            .map(|chain| chain.target.0.to_string())
+            .collect();
+        let mut unique = targets.clone();
+        unique.dedup();
+
+        assert!(targets.contains(&"webpack/runtime/define property getters".to_string()));
+        assert_eq!(targets, unique);
+    }
+
+    #[test]
+    fn all_entrypoints_match_single_traversals() {
+        let stats: Stats = serde_json::from_str(SOURCE_FILE).unwrap();
+        let mut entrypoints = stats.entrypoints.values().collect::<Vec<_>>();
+        entrypoints.sort_by_key(|entrypoint| entrypoint.name());
+
+        let all = traverse_all_entrypoints(&stats.modules, &stats.chunks, &entrypoints);
+        assert_eq!(all.len(), 2);
+        for ((name, graph), entrypoint) in all.into_iter().zip(entrypoints) {
+            assert_eq!(name, entrypoint.name());
+            let single = traverse_entry_chunk(&stats.modules, &stats.chunks, entrypoint).unwrap();
+            let graph = summarize(&graph.unwrap());
+            assert!(!graph.is_empty());
+            assert_eq!(graph, summarize(&single));
+        }
+    }
+
+    fn build_sizes(stats: &Stats) -> BuildSizes {
+        let entrypoints = stats.entrypoints.values().collect::<Vec<_>>();
+        BuildSizes::new(&entrypoints, &stats.assets, &stats.chunks, &stats.modules)
+    }
+
+    #[test]
+    fn diff_named_chunks() {
+        let old: Stats = serde_json::from_str(SOURCE_FILE).unwrap();
+        let new: Stats = serde_json::from_str(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../webpack-stats/test_projects/v5-named-chunks/compilation-stats.json"
+        )))
+        .unwrap();
+        let old = build_sizes(&old);
+
+        // Runtime modules are listed once per chunk and concatenated modules
+        // are part of the module they are concatenated into
+        let jsonp = ModuleIdentifier("webpack/runtime/jsonp chunk loading".into());
+        assert_eq!(old.modules[&jsonp], SizeBytes(3082.0 + 3073.0));
+        let total = old.modules.values().map(|size| size.0).sum::<f32>();
+        assert_eq!(total, 19754.0);
+
+        let diff = diff_stats(&old, &build_sizes(&new));
+        assert!(diff.modules.is_empty(), "{:?}", diff.modules);
+        assert!(diff.assets.is_empty());
+        assert!(diff.entrypoints.is_empty());
+        assert_eq!(
+            diff.chunks.get(&ChunkId::Number(826)),
+            Some(&SizeChange::Removed(SizeBytes(10741.0)))
+        );
+        assert_eq!(
+            diff.chunks.get(&ChunkId::from("index")),
+            Some(&SizeChange::Added(SizeBytes(10741.0)))
+        );
+    }
+}
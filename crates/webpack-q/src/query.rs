@@ -0,0 +1,1164 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A small query language over the modules and chunks of a compilation, for
+//! questions that no operation answers directly.
+//!
+//! ```text
+//! modules where size > 50KiB and chunk in entry("app").initial and name ~ "node_modules/moment"
+//! chunks where not initial = true order by size desc limit 5
+//! ```
+//!
+//! A query selects `modules` or `chunks`, optionally filtered by a `where`
+//! condition, sorted with `order by <field> [asc|desc]` and cut short with
+//! `limit <n>`. Results are sorted by size, largest first, unless an order is
+//! given. Conditions are comparisons joined with `and`, `or`, `not` and
+//! parentheses. The fields are
+//!
+//! * modules: `name`, `id`, `size`, `chunk` and `importers`, the number of
+//!   modules that import the module. Modules concatenated into another
+//!   module are not listed on their own
+//! * chunks: `id`, `size`, `initial`, `modules`, the number of modules in the
+//!   chunk, and `file`
+//!
+//! Text is compared with `=`, `!=`, `~` (contains) and `in`, numbers with
+//! `=`, `!=`, `<`, `<=`, `>` and `>=`. Sizes take the same units as budgets,
+//! e.g. `50KiB`. `in` takes a list such as `["a", "b"]` or, for chunks, the
+//! chunks of an entrypoint: `entry("app")` is every chunk it can load,
+//! `entry("app").initial` the chunks loaded synchronously and
+//! `entry("app").async` the others. Fields with several values, like the
+//! chunks of a module, match when any of the values does and `!=` matches
+//! when none does.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::{Display, Formatter};
+
+use meshed::graph::{Graph, GraphDefinition};
+use meshed::prelude::*;
+use serde::Serialize;
+use thiserror::Error;
+use webpack_stats::chunk::{Chunk, ChunkId, ChunkInitial, ChunkModules, Chunks, Files};
+use webpack_stats::entry::Entrypoint;
+use webpack_stats::module::{Module, ModuleChunks, ModuleIdentifier, ModuleName, Modules};
+use webpack_stats::SizeBytes;
+
+use crate::budgets::BudgetSize;
+use crate::graphs::ChunkLoadGraph;
+use crate::operations::{initial_chunks, reachable_chunks};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum QueryError {
+    #[error("Unexpected end of query, expected {0}")]
+    UnexpectedEnd(&'static str),
+    #[error("Unexpected {found} at position {position}, expected {expected}")]
+    Unexpected {
+        found: String,
+        position: usize,
+        expected: &'static str,
+    },
+    #[error("Unterminated string at position {0}")]
+    UnterminatedString(usize),
+    #[error("{target} have no field {field}")]
+    UnknownField { target: Target, field: String },
+    #[error("{field} cannot be compared with {operator}")]
+    InvalidOperator { field: Field, operator: Operator },
+    #[error("{field} cannot be compared with {value}")]
+    InvalidValue { field: Field, value: String },
+    #[error("Cannot order by {0}, it has several values")]
+    InvalidOrder(Field),
+    #[error("Entrypoint {0} does not exist")]
+    NoEntrypoint(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Target {
+    Modules,
+    Chunks,
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Target::Modules => "modules",
+            Target::Chunks => "chunks",
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Field {
+    Name,
+    Id,
+    Size,
+    Chunk,
+    Importers,
+    Initial,
+    Modules,
+    File,
+}
+
+/// What a field holds, which decides the operators and values it takes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    Text,
+    Texts,
+    Size,
+    Count,
+    Boolean,
+    Chunk,
+    Chunks,
+}
+
+impl Field {
+    fn parse(word: &str) -> Option<Self> {
+        Some(match word {
+            "name" => Field::Name,
+            "id" => Field::Id,
+            "size" => Field::Size,
+            "chunk" => Field::Chunk,
+            "importers" => Field::Importers,
+            "initial" => Field::Initial,
+            "modules" => Field::Modules,
+            "file" => Field::File,
+            _ => return None,
+        })
+    }
+
+    /// `None` when the target has no such field
+    fn kind(self, target: Target) -> Option<Kind> {
+        Some(match (target, self) {
+            (_, Field::Size) => Kind::Size,
+            (Target::Modules, Field::Name) | (Target::Modules, Field::Id) => Kind::Text,
+            (Target::Modules, Field::Chunk) => Kind::Chunks,
+            (Target::Modules, Field::Importers) => Kind::Count,
+            (Target::Chunks, Field::Id) => Kind::Chunk,
+            (Target::Chunks, Field::Initial) => Kind::Boolean,
+            (Target::Chunks, Field::Modules) => Kind::Count,
+            (Target::Chunks, Field::File) => Kind::Texts,
+            _ => return None,
+        })
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Field::Name => "name",
+            Field::Id => "id",
+            Field::Size => "size",
+            Field::Chunk => "chunk",
+            Field::Importers => "importers",
+            Field::Initial => "initial",
+            Field::Modules => "modules",
+            Field::File => "file",
+        })
+    }
+}
+
+impl Kind {
+    fn accepts(self, operator: Operator) -> bool {
+        use Operator::*;
+        match self {
+            Kind::Text | Kind::Texts => matches!(operator, Equal | NotEqual | Contains | In),
+            Kind::Size | Kind::Count => !matches!(operator, Contains | In),
+            Kind::Boolean => matches!(operator, Equal | NotEqual),
+            Kind::Chunk | Kind::Chunks => matches!(operator, Equal | NotEqual | In),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Contains,
+    In,
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Operator::Equal => "=",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::LessOrEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Contains => "~",
+            Operator::In => "in",
+        })
+    }
+}
+
+/// Which chunks of an entrypoint `entry(..)` stands for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum EntryChunks {
+    All,
+    Initial,
+    Async,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    /// Bytes for sizes
+    Number(f32),
+    Text(String),
+    Boolean(bool),
+    List(BTreeSet<String>),
+    /// Replaced by the list of its chunks before the query runs
+    Entry {
+        name: String,
+        chunks: EntryChunks,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+    Compare {
+        field: Field,
+        operator: Operator,
+        value: Value,
+    },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Order {
+    field: Field,
+    descending: bool,
+}
+
+/// A parsed query, see the [module documentation](self)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selection {
+    target: Target,
+    condition: Option<Condition>,
+    order: Option<Order>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    /// Kept as written. Sizes carry their unit, e.g. `50KiB`
+    Number(String),
+    Text(String),
+    Symbol(&'static str),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Word(word) => f.write_str(word),
+            Token::Number(number) => f.write_str(number),
+            Token::Text(text) => write!(f, "{:?}", text),
+            Token::Symbol(symbol) => write!(f, "`{}`", symbol),
+        }
+    }
+}
+
+/// Longest symbols first, so that `<=` is not read as `<`
+const SYMBOLS: &[&str] = &[
+    "!=", "<=", ">=", "=", "<", ">", "~", "(", ")", "[", "]", ",", ".",
+];
+
+/// Split a query into tokens and their byte positions
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let mut tokens = vec![];
+    let mut characters = query.char_indices().peekable();
+    while let Some(&(position, character)) = characters.peek() {
+        if character.is_whitespace() {
+            characters.next();
+        } else if character == '"' {
+            characters.next();
+            let mut text = String::new();
+            loop {
+                match characters.next() {
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => match characters.next() {
+                        Some((_, escaped)) => text.push(escaped),
+                        None => return Err(QueryError::UnterminatedString(position)),
+                    },
+                    Some((_, character)) => text.push(character),
+                    None => return Err(QueryError::UnterminatedString(position)),
+                }
+            }
+            tokens.push((position, Token::Text(text)));
+        } else if character.is_alphanumeric() || character == '_' {
+            let mut word = String::new();
+            while let Some(&(_, character)) = characters.peek() {
+                let number = word.starts_with(|c: char| c.is_ascii_digit());
+                if character.is_alphanumeric() || character == '_' || (number && character == '.') {
+                    word.push(character);
+                    characters.next();
+                } else {
+                    break;
+                }
+            }
+            if word.starts_with(|c: char| c.is_ascii_digit()) {
+                tokens.push((position, Token::Number(word)));
+            } else {
+                tokens.push((position, Token::Word(word)));
+            }
+        } else {
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| query[position..].starts_with(**symbol))
+                .ok_or_else(|| QueryError::Unexpected {
+                    found: format!("`{}`", character),
+                    position,
+                    expected: "a field, an operator or a value",
+                })?;
+            for _ in 0..symbol.len() {
+                characters.next();
+            }
+            tokens.push((position, Token::Symbol(symbol)));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    target: Target,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn unexpected(&self, expected: &'static str) -> QueryError {
+        match self.tokens.get(self.next) {
+            Some((position, token)) => QueryError::Unexpected {
+                found: token.to_string(),
+                position: *position,
+                expected,
+            },
+            None => QueryError::UnexpectedEnd(expected),
+        }
+    }
+
+    fn advance(&mut self, expected: &'static str) -> Result<Token, QueryError> {
+        let token = self
+            .peek()
+            .cloned()
+            .ok_or(QueryError::UnexpectedEnd(expected))?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    /// Consume the keyword if it is next. Keywords are case insensitive
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.next += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &'static str) -> Result<(), QueryError> {
+        if self.keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(keyword))
+        }
+    }
+
+    fn symbol(&mut self, symbol: &'static str) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.next += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &'static str) -> Result<(), QueryError> {
+        if self.symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(symbol))
+        }
+    }
+
+    fn selection(&mut self) -> Result<Selection, QueryError> {
+        let condition = if self.keyword("where") {
+            Some(self.or()?)
+        } else {
+            None
+        };
+
+        let order = if self.keyword("order") {
+            self.expect_keyword("by")?;
+            let (field, kind) = self.field()?;
+            if matches!(kind, Kind::Texts | Kind::Chunks) {
+                return Err(QueryError::InvalidOrder(field));
+            }
+            let descending = self.keyword("desc");
+            if !descending {
+                self.keyword("asc");
+            }
+            Some(Order { field, descending })
+        } else {
+            None
+        };
+
+        let limit = if self.keyword("limit") {
+            match self.advance("a number")? {
+                Token::Number(number) => Some(number.parse().map_err(|_| {
+                    self.next -= 1;
+                    self.unexpected("a number")
+                })?),
+                _ => {
+                    self.next -= 1;
+                    return Err(self.unexpected("a number"));
+                }
+            }
+        } else {
+            None
+        };
+
+        if self.peek().is_some() {
+            return Err(self.unexpected("and, or, order by, limit or the end of the query"));
+        }
+
+        Ok(Selection {
+            target: self.target,
+            condition,
+            order,
+            limit,
+        })
+    }
+
+    fn or(&mut self) -> Result<Condition, QueryError> {
+        let mut condition = self.and()?;
+        while self.keyword("or") {
+            condition = Condition::Or(Box::new(condition), Box::new(self.and()?));
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<Condition, QueryError> {
+        let mut condition = self.not()?;
+        while self.keyword("and") {
+            condition = Condition::And(Box::new(condition), Box::new(self.not()?));
+        }
+        Ok(condition)
+    }
+
+    fn not(&mut self) -> Result<Condition, QueryError> {
+        if self.keyword("not") {
+            Ok(Condition::Not(Box::new(self.not()?)))
+        } else if self.symbol("(") {
+            let condition = self.or()?;
+            self.expect_symbol(")")?;
+            Ok(condition)
+        } else {
+            self.comparison()
+        }
+    }
+
+    fn field(&mut self) -> Result<(Field, Kind), QueryError> {
+        match self.advance("a field")? {
+            Token::Word(word) => {
+                let field = Field::parse(&word.to_ascii_lowercase());
+                field
+                    .and_then(|field| Some((field, field.kind(self.target)?)))
+                    .ok_or(QueryError::UnknownField {
+                        target: self.target,
+                        field: word,
+                    })
+            }
+            _ => {
+                self.next -= 1;
+                Err(self.unexpected("a field"))
+            }
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Condition, QueryError> {
+        let (field, kind) = self.field()?;
+
+        let operator = match self.advance("an operator")? {
+            Token::Symbol("=") => Operator::Equal,
+            Token::Symbol("!=") => Operator::NotEqual,
+            Token::Symbol("<") => Operator::Less,
+            Token::Symbol("<=") => Operator::LessOrEqual,
+            Token::Symbol(">") => Operator::Greater,
+            Token::Symbol(">=") => Operator::GreaterOrEqual,
+            Token::Symbol("~") => Operator::Contains,
+            Token::Word(word) if word.eq_ignore_ascii_case("in") => Operator::In,
+            _ => {
+                self.next -= 1;
+                return Err(self.unexpected("an operator"));
+            }
+        };
+        if !kind.accepts(operator) {
+            return Err(QueryError::InvalidOperator { field, operator });
+        }
+
+        let value = if operator == Operator::In {
+            self.list(field, kind)?
+        } else {
+            self.value(field, kind)?
+        };
+
+        Ok(Condition::Compare {
+            field,
+            operator,
+            value,
+        })
+    }
+
+    fn value(&mut self, field: Field, kind: Kind) -> Result<Value, QueryError> {
+        let token = self.advance("a value")?;
+        let value = match (kind, &token) {
+            (Kind::Size, Token::Number(number)) => {
+                BudgetSize::parse(number).map(|size| Value::Number(size.0 .0))
+            }
+            (Kind::Count, Token::Number(number)) => number
+                .parse::<usize>()
+                .ok()
+                .map(|count| Value::Number(count as f32)),
+            (Kind::Boolean, Token::Word(word)) if word.eq_ignore_ascii_case("true") => {
+                Some(Value::Boolean(true))
+            }
+            (Kind::Boolean, Token::Word(word)) if word.eq_ignore_ascii_case("false") => {
+                Some(Value::Boolean(false))
+            }
+            (Kind::Text | Kind::Texts, Token::Text(text)) => Some(Value::Text(text.clone())),
+            (Kind::Chunk | Kind::Chunks, Token::Number(id) | Token::Text(id)) => {
+                Some(Value::Text(id.clone()))
+            }
+            _ => None,
+        };
+
+        value.ok_or_else(|| QueryError::InvalidValue {
+            field,
+            value: token.to_string(),
+        })
+    }
+
+    fn list(&mut self, field: Field, kind: Kind) -> Result<Value, QueryError> {
+        if self.symbol("[") {
+            let mut values = BTreeSet::new();
+            if !self.symbol("]") {
+                loop {
+                    match self.value(field, kind)? {
+                        Value::Text(text) => values.insert(text),
+                        _ => unreachable!("in is only accepted by text and chunk fields"),
+                    };
+                    if self.symbol("]") {
+                        break;
+                    }
+                    self.expect_symbol(",")?;
+                }
+            }
+            return Ok(Value::List(values));
+        }
+
+        if !matches!(kind, Kind::Chunk | Kind::Chunks) || !self.keyword("entry") {
+            return Err(
+                self.unexpected(if matches!(kind, Kind::Chunk | Kind::Chunks) {
+                    "a list or entry(..)"
+                } else {
+                    "a list"
+                }),
+            );
+        }
+        self.expect_symbol("(")?;
+        let name = match self.advance("an entrypoint name")? {
+            Token::Text(name) => name,
+            _ => {
+                self.next -= 1;
+                return Err(self.unexpected("an entrypoint name"));
+            }
+        };
+        self.expect_symbol(")")?;
+
+        let chunks = if self.symbol(".") {
+            if self.keyword("initial") {
+                EntryChunks::Initial
+            } else if self.keyword("async") {
+                EntryChunks::Async
+            } else {
+                return Err(self.unexpected("initial or async"));
+            }
+        } else {
+            EntryChunks::All
+        };
+
+        Ok(Value::Entry { name, chunks })
+    }
+}
+
+impl Selection {
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser {
+            tokens,
+            next: 0,
+            target: Target::Modules,
+        };
+        parser.target = if parser.keyword("modules") {
+            Target::Modules
+        } else if parser.keyword("chunks") {
+            Target::Chunks
+        } else {
+            return Err(parser.unexpected("modules or chunks"));
+        };
+
+        parser.selection()
+    }
+
+    pub fn target(&self) -> Target {
+        self.target
+    }
+
+    /// Run the query against a compilation. `chunk_loads` resolves the chunks
+    /// of `entry(..)`
+    pub fn evaluate<E, M, Mv, C, Cv>(
+        &self,
+        entrypoints: &[&E],
+        modules: &M,
+        chunks: &C,
+        chunk_loads: &Graph<ChunkLoadGraph>,
+    ) -> Result<Selected, QueryError>
+    where
+        E: Entrypoint,
+        M: Modules<Mv>,
+        Mv: Module,
+        C: Chunks<Cv>,
+        Cv: Chunk,
+    {
+        let mut condition = self.condition.clone();
+        if let Some(condition) = condition.as_mut() {
+            condition.resolve(&|name, part| {
+                let entrypoint = entrypoints
+                    .iter()
+                    .find(|entrypoint| entrypoint.name() == name)
+                    .ok_or_else(|| QueryError::NoEntrypoint(name.to_string()))?;
+                let entry_chunks = entrypoint.chunks();
+                let initial = || {
+                    let mut initial = initial_chunks(chunk_loads, entry_chunks);
+                    initial.extend(entry_chunks.iter().cloned());
+                    initial
+                };
+                let all = || {
+                    let mut all = reachable_chunks(chunk_loads, entry_chunks);
+                    all.extend(entry_chunks.iter().cloned());
+                    all
+                };
+                let chunks: HashSet<ChunkId> = match part {
+                    EntryChunks::All => all(),
+                    EntryChunks::Initial => initial(),
+                    EntryChunks::Async => &all() - &initial(),
+                };
+                Ok(chunks.iter().map(ToString::to_string).collect())
+            })?;
+        }
+
+        Ok(match self.target {
+            Target::Modules => {
+                // Concatenated modules are part of the module they were
+                // concatenated into, listing them too counts them twice
+                let rows = modules
+                    .top_level()
+                    .into_iter()
+                    .map(ModuleRow::new)
+                    .collect();
+                Selected::Modules(self.finish(rows, condition.as_ref()))
+            }
+            Target::Chunks => {
+                let rows = chunks.all().into_iter().map(ChunkRow::new).collect();
+                Selected::Chunks(self.finish(rows, condition.as_ref()))
+            }
+        })
+    }
+
+    fn finish<R: Row>(&self, rows: Vec<R>, condition: Option<&Condition>) -> Vec<R> {
+        let mut rows: Vec<_> = rows
+            .into_iter()
+            .filter(|row| {
+                condition
+                    .map(|condition| condition.matches(row))
+                    .unwrap_or(true)
+            })
+            .collect();
+
+        let order = self.order.unwrap_or(Order {
+            field: Field::Size,
+            descending: true,
+        });
+        // Sorting is stable, so ties stay in the order of the first sort
+        rows.sort_by(|a, b| compare_values(&a.value(R::KEY), &b.value(R::KEY)));
+        rows.sort_by(|a, b| {
+            let ordering = compare_values(&a.value(order.field), &b.value(order.field));
+            if order.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        if let Some(limit) = self.limit {
+            rows.truncate(limit);
+        }
+        rows
+    }
+}
+
+/// Parse and run a query against a compilation, see [`Selection`]
+pub fn select<E, M, Mv, C, Cv>(
+    query: &str,
+    entrypoints: &[&E],
+    modules: &M,
+    chunks: &C,
+) -> Result<Selected, QueryError>
+where
+    E: Entrypoint,
+    M: Modules<Mv>,
+    Mv: Module,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+{
+    let graph = ChunkLoadGraph::build_graph(chunks);
+    Selection::parse(query)?.evaluate(entrypoints, modules, chunks, &graph)
+}
+
+impl Condition {
+    fn resolve<F>(&mut self, entry_chunks: &F) -> Result<(), QueryError>
+    where
+        F: Fn(&str, EntryChunks) -> Result<BTreeSet<String>, QueryError>,
+    {
+        match self {
+            Condition::And(a, b) | Condition::Or(a, b) => {
+                a.resolve(entry_chunks)?;
+                b.resolve(entry_chunks)
+            }
+            Condition::Not(condition) => condition.resolve(entry_chunks),
+            Condition::Compare { value, .. } => {
+                if let Value::Entry { name, chunks } = value {
+                    *value = Value::List(entry_chunks(name, *chunks)?);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn matches<R: Row>(&self, row: &R) -> bool {
+        match self {
+            Condition::And(a, b) => a.matches(row) && b.matches(row),
+            Condition::Or(a, b) => a.matches(row) || b.matches(row),
+            Condition::Not(condition) => !condition.matches(row),
+            Condition::Compare {
+                field,
+                operator,
+                value,
+            } => compare(&row.value(*field), *operator, value),
+        }
+    }
+}
+
+/// The value of a field in a row
+enum FieldValue<'r> {
+    Number(f32),
+    Boolean(bool),
+    Text(Cow<'r, str>),
+    Texts(Vec<Cow<'r, str>>),
+}
+
+fn compare(actual: &FieldValue, operator: Operator, expected: &Value) -> bool {
+    match (actual, expected) {
+        (FieldValue::Number(actual), Value::Number(expected)) => match operator {
+            Operator::Equal => actual == expected,
+            Operator::NotEqual => actual != expected,
+            Operator::Less => actual < expected,
+            Operator::LessOrEqual => actual <= expected,
+            Operator::Greater => actual > expected,
+            Operator::GreaterOrEqual => actual >= expected,
+            _ => false,
+        },
+        (FieldValue::Boolean(actual), Value::Boolean(expected)) => match operator {
+            Operator::Equal => actual == expected,
+            Operator::NotEqual => actual != expected,
+            _ => false,
+        },
+        (FieldValue::Text(actual), _) => compare_text(actual, operator, expected),
+        (FieldValue::Texts(values), _) if operator == Operator::NotEqual => !values
+            .iter()
+            .any(|actual| compare_text(actual, Operator::Equal, expected)),
+        (FieldValue::Texts(values), _) => values
+            .iter()
+            .any(|actual| compare_text(actual, operator, expected)),
+        _ => false,
+    }
+}
+
+fn compare_text(actual: &str, operator: Operator, expected: &Value) -> bool {
+    match (operator, expected) {
+        (Operator::Equal, Value::Text(expected)) => actual == expected,
+        (Operator::NotEqual, Value::Text(expected)) => actual != expected,
+        (Operator::Contains, Value::Text(expected)) => actual.contains(expected.as_str()),
+        (Operator::In, Value::List(expected)) => expected.contains(actual),
+        _ => false,
+    }
+}
+
+/// Numeric text, like chunk ids, is ordered by its value
+fn compare_values(a: &FieldValue, b: &FieldValue) -> Ordering {
+    match (a, b) {
+        (FieldValue::Number(a), FieldValue::Number(b)) => {
+            a.partial_cmp(b).unwrap_or(Ordering::Equal)
+        }
+        (FieldValue::Boolean(a), FieldValue::Boolean(b)) => a.cmp(b),
+        (FieldValue::Text(a), FieldValue::Text(b)) => match (a.parse::<f64>(), b.parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            _ => a.cmp(b),
+        },
+        _ => Ordering::Equal,
+    }
+}
+
+trait Row {
+    /// Orders rows that tie on the requested order
+    const KEY: Field;
+
+    fn value(&self, field: Field) -> FieldValue<'_>;
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModuleRow {
+    pub id: ModuleIdentifier,
+    pub name: ModuleName,
+    pub size: SizeBytes,
+    pub chunks: Vec<ChunkId>,
+    pub importers: usize,
+}
+
+impl ModuleRow {
+    fn new<Mv: Module>(module: &Mv) -> Self {
+        let id = module.get_id();
+        let mut chunks: Vec<_> = ExtractData::<ModuleChunks>::extract_data(module)
+            .into_iter()
+            .collect();
+        chunks.sort();
+        let importers: HashSet<_> = module
+            .edges()
+            .map(|edge| edge.sink)
+            .filter(|importer| importer != &id && !importer.0.is_empty())
+            .collect();
+
+        Self {
+            name: module.label(),
+            size: module.extract_data(),
+            chunks,
+            importers: importers.len(),
+            id,
+        }
+    }
+}
+
+impl Row for ModuleRow {
+    const KEY: Field = Field::Name;
+
+    fn value(&self, field: Field) -> FieldValue<'_> {
+        match field {
+            Field::Name => FieldValue::Text(Cow::Borrowed(&self.name.0)),
+            Field::Id => FieldValue::Text(Cow::Borrowed(&self.id.0)),
+            Field::Size => FieldValue::Number(self.size.0),
+            Field::Chunk => FieldValue::Texts(
+                self.chunks
+                    .iter()
+                    .map(|chunk| Cow::Owned(chunk.to_string()))
+                    .collect(),
+            ),
+            Field::Importers => FieldValue::Number(self.importers as f32),
+            _ => FieldValue::Texts(vec![]),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChunkRow {
+    pub id: ChunkId,
+    pub size: SizeBytes,
+    pub initial: bool,
+    pub modules: usize,
+    pub files: Files,
+}
+
+impl ChunkRow {
+    fn new<Cv: Chunk>(chunk: &Cv) -> Self {
+        let initial: ChunkInitial = chunk.extract_data();
+        let modules: ChunkModules = chunk.extract_data();
+        Self {
+            id: chunk.get_id(),
+            size: chunk.extract_data(),
+            initial: initial.0,
+            modules: modules.len(),
+            files: chunk.extract_data(),
+        }
+    }
+}
+
+impl Row for ChunkRow {
+    const KEY: Field = Field::Id;
+
+    fn value(&self, field: Field) -> FieldValue<'_> {
+        match field {
+            Field::Id => FieldValue::Text(Cow::Owned(self.id.to_string())),
+            Field::Size => FieldValue::Number(self.size.0),
+            Field::Initial => FieldValue::Boolean(self.initial),
+            Field::Modules => FieldValue::Number(self.modules as f32),
+            Field::File => FieldValue::Texts(
+                self.files
+                    .0
+                    .iter()
+                    .map(|file| Cow::Borrowed(file.as_str()))
+                    .collect(),
+            ),
+            _ => FieldValue::Texts(vec![]),
+        }
+    }
+}
+
+/// The rows a query selected. Serializes as a list of rows and displays as
+/// a table
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Selected {
+    Modules(Vec<ModuleRow>),
+    Chunks(Vec<ChunkRow>),
+}
+
+impl Selected {
+    pub fn len(&self) -> usize {
+        match self {
+            Selected::Modules(rows) => rows.len(),
+            Selected::Chunks(rows) => rows.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn join<T: Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Left aligned columns. The last column is not padded
+fn write_table(
+    f: &mut Formatter<'_>,
+    headers: &[&str],
+    rows: Vec<Vec<String>>,
+) -> std::fmt::Result {
+    let mut widths: Vec<_> = headers.iter().map(|header| header.len()).collect();
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let headers = headers.iter().map(|header| header.to_string()).collect();
+    for row in std::iter::once(headers).chain(rows) {
+        let last = row.len() - 1;
+        for (column, cell) in row.iter().enumerate() {
+            if column == last {
+                writeln!(f, "{}", cell)?;
+            } else {
+                write!(f, "{:width$}  ", cell, width = widths[column])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Display for Selected {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (count, total) = match self {
+            Selected::Modules(rows) => {
+                let table = rows
+                    .iter()
+                    .map(|row| {
+                        vec![
+                            row.size.to_string(),
+                            join(&row.chunks),
+                            row.importers.to_string(),
+                            row.name.0.to_string(),
+                        ]
+                    })
+                    .collect();
+                write_table(f, &["size", "chunks", "importers", "name"], table)?;
+                let total = rows
+                    .iter()
+                    .fold(SizeBytes::default(), |acc, row| acc + row.size);
+                (format!("{} modules", rows.len()), total)
+            }
+            Selected::Chunks(rows) => {
+                let table = rows
+                    .iter()
+                    .map(|row| {
+                        vec![
+                            row.id.to_string(),
+                            row.size.to_string(),
+                            row.initial.to_string(),
+                            row.modules.to_string(),
+                            join(&row.files.0),
+                        ]
+                    })
+                    .collect();
+                write_table(f, &["id", "size", "initial", "modules", "files"], table)?;
+                let total = rows
+                    .iter()
+                    .fold(SizeBytes::default(), |acc, row| acc + row.size);
+                (format!("{} chunks", rows.len()), total)
+            }
+        };
+        writeln!(f, "{}, {} in total", count, total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webpack_stats::v5::Stats;
+
+    static SOURCE_FILE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../webpack-stats/test_projects/v5/compilation-stats.json"
+    ));
+
+    fn run(query: &str) -> Result<Selected, QueryError> {
+        let stats: Stats = serde_json::from_str(SOURCE_FILE).unwrap();
+        let entrypoints: Vec<_> = stats.entrypoints.values().collect();
+        select(query, &entrypoints, &stats.modules, &stats.chunks)
+    }
+
+    fn chunk_ids(selected: Selected) -> Vec<String> {
+        match selected {
+            Selected::Chunks(rows) => rows.iter().map(|row| row.id.to_string()).collect(),
+            Selected::Modules(_) => panic!("expected chunks"),
+        }
+    }
+
+    #[test]
+    fn selects_entry_chunks() {
+        let all = chunk_ids(run(r#"chunks where id in entry("index") order by id"#).unwrap());
+        let initial = chunk_ids(run(r#"chunks where id in entry("index").initial"#).unwrap());
+        let asynchronous =
+            chunk_ids(run(r#"chunks where id in entry("index").async order by id"#).unwrap());
+
+        assert_eq!(all, ["1", "826", "960"]);
+        assert_eq!(initial, ["826"]);
+        assert_eq!(asynchronous, ["1", "960"]);
+    }
+
+    #[test]
+    fn concatenated_modules_are_counted_once() {
+        let rows = match run("modules").unwrap() {
+            Selected::Modules(rows) => rows,
+            Selected::Chunks(_) => panic!("expected modules"),
+        };
+        let total = rows.iter().map(|row| row.size.0).sum::<f32>();
+        assert_eq!(rows.len(), 26);
+        assert_eq!(total, 19754.0);
+
+        let module2 = match run(r#"modules where name ~ "module2""#).unwrap() {
+            Selected::Modules(rows) => rows,
+            Selected::Chunks(_) => panic!("expected modules"),
+        };
+        assert_eq!(module2.len(), 1);
+    }
+
+    #[test]
+    fn filters_and_orders_modules() {
+        let selected = run(
+            r#"modules where chunk in entry("index").initial and (name ~ "utils" or size > 1KiB) order by name limit 2"#,
+        )
+        .unwrap();
+        let names: Vec<_> = match &selected {
+            Selected::Modules(rows) => rows.iter().map(|row| row.name.0.to_string()).collect(),
+            Selected::Chunks(_) => panic!("expected modules"),
+        };
+
+        assert_eq!(names, ["./index.js + 1 modules", "./utils.js"]);
+        assert_eq!(
+            selected.to_string().lines().last(),
+            Some("2 modules, 2.180664 KiB in total")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_queries() {
+        assert_eq!(
+            Selection::parse("files").unwrap_err(),
+            QueryError::Unexpected {
+                found: "files".to_string(),
+                position: 0,
+                expected: "modules or chunks",
+            }
+        );
+        assert_eq!(
+            Selection::parse("modules where initial = true").unwrap_err(),
+            QueryError::UnknownField {
+                target: Target::Modules,
+                field: "initial".to_string(),
+            }
+        );
+        assert_eq!(
+            Selection::parse("modules where size ~ 10").unwrap_err(),
+            QueryError::InvalidOperator {
+                field: Field::Size,
+                operator: Operator::Contains,
+            }
+        );
+        assert_eq!(
+            Selection::parse("chunks where size > big").unwrap_err(),
+            QueryError::InvalidValue {
+                field: Field::Size,
+                value: "big".to_string(),
+            }
+        );
+        assert_eq!(
+            Selection::parse(r#"modules where name = "a"#).unwrap_err(),
+            QueryError::UnterminatedString(21)
+        );
+        assert!(matches!(
+            run(r#"chunks where id in entry("missing")"#),
+            Err(QueryError::NoEntrypoint(_))
+        ));
+    }
+}
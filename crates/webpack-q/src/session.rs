@@ -0,0 +1,295 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A compilation that is loaded once and queried many times, e.g. by
+//! `webpack-q serve`. The graphs are built up front, so a query only pays
+//! for its own traversal.
+
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use meshed::graph::{Graph, Inverted};
+use meshed::prelude::*;
+use serde::Serialize;
+use thiserror::Error;
+use webpack_stats::chunk::{Chunk, ChunkId, Chunks};
+use webpack_stats::entry::Entrypoint;
+use webpack_stats::module::{Module, ModuleChunks, ModuleIdentifier, ModuleName, Modules};
+use webpack_stats::SizeBytes;
+
+use crate::compressed::CompressionEstimator;
+use crate::graphs::{ChunkLoadGraph, ModuleParentGraph};
+use crate::operations::{
+    describe_chunk, describe_entrypoints, display_entrypoints, initial_load_size, why_module,
+    ChunkDescription, CompilationGraphs, EntrypointDescription, EntrypointDescriptionError,
+    EntrypointTraversalError, ModuleInclusion,
+};
+use crate::query::{QueryError, Selected, Selection};
+
+#[derive(Debug, Error)]
+pub enum SessionError {
+    #[error("Entrypoint {0} does not exist")]
+    NoEntrypoint(String),
+    #[error("Chunk {0} does not exist")]
+    NoChunk(ChunkId),
+    #[error(transparent)]
+    Traversal(#[from] EntrypointTraversalError),
+    #[error(transparent)]
+    Description(#[from] EntrypointDescriptionError),
+    #[error(transparent)]
+    Query(#[from] QueryError),
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntrypointSummary {
+    pub name: String,
+    pub chunks: Vec<ChunkId>,
+    pub initial_size: SizeBytes,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModuleMatch {
+    pub id: ModuleIdentifier,
+    pub name: ModuleName,
+    pub chunks: Vec<ChunkId>,
+    pub size: SizeBytes,
+}
+
+/// `Mv` and `Cv` are the module and chunk types of the stats version
+pub struct Session<'a, M, Mv, C, Cv, E> {
+    modules: &'a M,
+    chunks: &'a C,
+    entrypoints: BTreeMap<&'a str, &'a E>,
+    estimator: CompressionEstimator<'a>,
+    graphs: CompilationGraphs,
+    values: PhantomData<(&'a Mv, &'a Cv)>,
+}
+
+impl<'a, M, Mv, C, Cv, E> Session<'a, M, Mv, C, Cv, E>
+where
+    M: Modules<Mv>,
+    Mv: Module + 'a,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+    E: Entrypoint,
+{
+    pub fn new(
+        modules: &'a M,
+        chunks: &'a C,
+        entrypoints: impl IntoIterator<Item = &'a E>,
+        estimator: CompressionEstimator<'a>,
+    ) -> Self {
+        Self {
+            modules,
+            chunks,
+            entrypoints: entrypoints
+                .into_iter()
+                .map(|entrypoint| (entrypoint.name(), entrypoint))
+                .collect(),
+            estimator,
+            graphs: CompilationGraphs::build(modules, chunks),
+            values: PhantomData,
+        }
+    }
+
+    pub fn modules(&self) -> &'a M {
+        self.modules
+    }
+
+    pub fn chunks(&self) -> &'a C {
+        self.chunks
+    }
+
+    fn entry_chunks(&self, entrypoint: &str) -> Result<&'a [ChunkId], SessionError> {
+        self.entrypoints
+            .get(entrypoint)
+            .map(|entrypoint| entrypoint.chunks())
+            .ok_or_else(|| SessionError::NoEntrypoint(entrypoint.to_string()))
+    }
+
+    pub fn entrypoints(&self) -> Vec<EntrypointSummary> {
+        self.entrypoints
+            .values()
+            .map(|entrypoint| EntrypointSummary {
+                name: entrypoint.name().to_string(),
+                chunks: entrypoint.chunks().to_vec(),
+                initial_size: initial_load_size(&self.graphs.chunk_loads, entrypoint.chunks()),
+            })
+            .collect()
+    }
+
+    pub fn describe_entrypoint<'n>(
+        &self,
+        entrypoint: &'n str,
+    ) -> Result<EntrypointDescription<'n>, SessionError> {
+        let entrypoints: Vec<_> = self.entrypoints.values().copied().collect();
+        Ok(describe_entrypoints(
+            self.chunks,
+            entrypoint,
+            display_entrypoints(&entrypoints),
+            &self.estimator,
+        )?)
+    }
+
+    pub fn describe_chunk(&self, chunk: ChunkId) -> Result<ChunkDescription, SessionError> {
+        let chunk = self.chunks.resolve_id(chunk);
+        describe_chunk(chunk.clone(), self.chunks, self.modules, &self.estimator)
+            .ok_or(SessionError::NoChunk(chunk))
+    }
+
+    /// Every module the entrypoint loads, annotated with module sizes
+    pub fn traverse_entrypoint(
+        &self,
+        entrypoint: &str,
+    ) -> Result<Inverted<ModuleParentGraph>, SessionError> {
+        let graph = self
+            .graphs
+            .traverse_entrypoint(self.entry_chunks(entrypoint)?)?;
+        self.estimator.annotate_modules(&graph, self.modules);
+        Ok(graph)
+    }
+
+    /// Every chunk the entrypoint loads
+    pub fn load_entry_chunks(
+        &self,
+        entrypoint: &str,
+    ) -> Result<Graph<ChunkLoadGraph>, SessionError> {
+        Ok(self
+            .graphs
+            .load_entry_chunks(self.entry_chunks(entrypoint)?))
+    }
+
+    pub fn paths_to_chunk(
+        &self,
+        entrypoint: &str,
+        chunk: ChunkId,
+    ) -> Result<Inverted<ModuleParentGraph>, SessionError> {
+        let entry_chunks = self.entry_chunks(entrypoint)?;
+        let chunk = self.chunks.resolve_id(chunk);
+        if self.graphs.chunks.query(&chunk).is_none() {
+            return Err(SessionError::NoChunk(chunk));
+        }
+        let graph = self.graphs.paths_to_chunk(entry_chunks, chunk);
+        self.estimator.annotate_modules(&graph, self.modules);
+        Ok(graph)
+    }
+
+    /// Import chains from the entrypoint to the modules matching `pattern`,
+    /// see [`why_module`]
+    pub fn why_module(
+        &self,
+        entrypoint: &str,
+        pattern: &str,
+    ) -> Result<ModuleInclusion, SessionError> {
+        let entrypoint = self
+            .entrypoints
+            .get(entrypoint)
+            .ok_or_else(|| SessionError::NoEntrypoint(entrypoint.to_string()))?;
+        let inclusion = why_module(*entrypoint, pattern, self.chunks, self.modules);
+        self.estimator
+            .annotate_modules(&inclusion.graph, self.modules);
+        Ok(inclusion)
+    }
+
+    /// Run a query, see [`crate::query`]
+    pub fn select(&self, query: &str) -> Result<Selected, SessionError> {
+        let entrypoints: Vec<_> = self.entrypoints.values().copied().collect();
+        Ok(Selection::parse(query)?.evaluate(
+            &entrypoints,
+            self.modules,
+            self.chunks,
+            &self.graphs.chunk_loads,
+        )?)
+    }
+
+    /// Modules whose name or identifier contains `pattern`, sorted by name
+    pub fn search_modules(&self, pattern: &str) -> Vec<ModuleMatch> {
+        let mut matches: Vec<_> = self
+            .modules
+            .all()
+            .into_iter()
+            .filter(|module| {
+                module.label().0.contains(pattern) || module.get_id().0.contains(pattern)
+            })
+            .map(|module| {
+                let mut chunks: Vec<_> = ExtractData::<ModuleChunks>::extract_data(module)
+                    .into_iter()
+                    .collect();
+                chunks.sort();
+                ModuleMatch {
+                    id: module.get_id(),
+                    name: module.label(),
+                    chunks,
+                    size: module.extract_data(),
+                }
+            })
+            .collect();
+        matches.sort_by(|a, b| a.name.0.cmp(&b.name.0));
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshed::graph::node::Node;
+    use std::path::Path;
+    use webpack_stats::v5::Stats;
+
+    static SOURCE_FILE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../webpack-stats/test_projects/v5/compilation-stats.json"
+    ));
+
+    fn chunks_of(graph: &Inverted<ModuleParentGraph>) -> Vec<(String, Option<ChunkId>)> {
+        let mut chunks: Vec<_> = graph
+            .inner()
+            .all_nodes()
+            .map(|node: Node<ModuleParentGraph>| {
+                (
+                    node.get_id().0.to_string(),
+                    node.get_annotation::<ChunkId>(),
+                )
+            })
+            .collect();
+        chunks.sort();
+        chunks
+    }
+
+    #[test]
+    fn queries_do_not_leak_into_each_other() {
+        let stats: Stats = serde_json::from_str(SOURCE_FILE).unwrap();
+        let estimator = CompressionEstimator::new(Path::new(""), &stats.modules);
+        let session = Session::new(
+            &stats.modules,
+            &stats.chunks,
+            stats.entrypoints.values(),
+            estimator,
+        );
+
+        let first = chunks_of(&session.traverse_entrypoint("index").unwrap());
+        session.paths_to_chunk("index", ChunkId::from(960)).unwrap();
+        session.traverse_entrypoint("entryTwo").unwrap();
+        let second = chunks_of(&session.traverse_entrypoint("index").unwrap());
+
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+        assert!(matches!(
+            session.traverse_entrypoint("missing"),
+            Err(SessionError::NoEntrypoint(_))
+        ));
+    }
+}
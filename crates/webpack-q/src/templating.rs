@@ -16,18 +16,36 @@
 
 use std::fs::{File, OpenOptions};
 use std::io;
-use std::io::BufWriter;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
+/// The html views. Each one reads the `data.json` written next to it, or the
+/// data inlined into the page by [`write_single_html_file`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Template {
+    /// Module graphs, see [`crate::graphs::ser`]
+    Graph,
+    /// Size trees, see [`crate::treemap`]
+    Treemap,
+}
+
+impl Template {
+    fn html(&self) -> &'static str {
+        match self {
+            Template::Graph => include_str!("../templates/index.html"),
+            Template::Treemap => include_str!("../templates/treemap.html"),
+        }
+    }
+}
+
 pub fn write_html_files_to_directory(
     directory: &Path,
-    write: impl FnOnce(&mut BufWriter<&mut File>) -> io::Result<()>,
+    template: Template,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
 ) -> std::io::Result<()> {
     let html_file = directory.join("index.html");
     let data_json = directory.join("data.json");
 
-    use std::io::Write;
-
     let mut html_file_handle = OpenOptions::new()
         .create(true)
         .write(true)
@@ -39,13 +57,79 @@ pub fn write_html_files_to_directory(
     html_file_handle.set_len(0)?;
     data_file_handle.set_len(0)?;
 
-    write!(
-        &mut html_file_handle,
-        "{}",
-        include_str!("../templates/index.html")
-    )?;
+    write!(&mut html_file_handle, "{}", template.html())?;
     let mut writer = BufWriter::new(&mut data_file_handle);
     write(&mut writer)?;
 
     Ok(())
 }
+
+/// Json inlined into a script tag must not contain `</script>`. `<` only
+/// shows up inside json strings, where `\u003c` is an equivalent escape
+struct ScriptSafe<W>(W);
+
+impl<W: Write> Write for ScriptSafe<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut parts = buf.split(|byte| *byte == b'<');
+        if let Some(first) = parts.next() {
+            self.0.write_all(first)?;
+        }
+        for part in parts {
+            self.0.write_all(b"\\u003c")?;
+            self.0.write_all(part)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Write the template with the data inlined into it. The page opens from
+/// `file://`, so it can be shared without a web server
+pub fn write_single_html_file(
+    path: &Path,
+    template: Template,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_single_html(&mut writer, template, write)?;
+    writer.flush()
+}
+
+/// Same as [`write_single_html_file`], for pages that are not written to disk
+pub fn write_single_html(
+    writer: &mut dyn Write,
+    template: Template,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> io::Result<()> {
+    let html = template.html();
+    let (head, rest) = html.split_at(html.find("</head>").unwrap_or(0));
+
+    writer.write_all(head.as_bytes())?;
+    writer.write_all(br#"<script id="webpack-q-data" type="application/json">"#)?;
+    write(&mut ScriptSafe(&mut *writer))?;
+    writer.write_all(b"</script>\n")?;
+    writer.write_all(rest.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inlined_json_cannot_close_the_script_tag() {
+        let mut output = vec![];
+        serde_json::to_writer(
+            ScriptSafe(&mut output),
+            &serde_json::json!({ "label": "</script><script>alert(1)" }),
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(!output.contains("</script>"));
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed["label"], "</script><script>alert(1)");
+    }
+}
@@ -0,0 +1,213 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Module sizes aggregated by path, the data behind a treemap.
+//!
+//! Modules from `node_modules` are grouped under the directory their package
+//! is installed in, e.g. `node_modules/@babel/runtime`, and then by the
+//! directories and files inside the package. Other modules are grouped by
+//! their path. Concatenated modules are replaced by the modules they contain.
+//! Modules that are not emitted in any chunk are left out.
+
+use std::collections::{BTreeMap, HashSet};
+
+use meshed::graph::GraphDefinition;
+use serde::Serialize;
+use webpack_stats::chunk::{Chunk, ChunkId, Chunks};
+use webpack_stats::module::{Module, ModuleChunks, Modules};
+use webpack_stats::SizeBytes;
+
+use crate::duplicates::PackagePath;
+use crate::graphs::ChunkLoadGraph;
+use crate::operations::{initial_chunks, reachable_chunks};
+
+/// A directory or a file and the size of every module inside it
+#[derive(Debug, Serialize)]
+pub struct SizeTree {
+    pub name: String,
+    pub size: SizeBytes,
+    /// Largest first. Empty for files
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SizeTree>,
+}
+
+#[derive(Default)]
+struct Directory {
+    size: f32,
+    children: BTreeMap<String, Directory>,
+}
+
+impl Directory {
+    fn insert(&mut self, path: &[String], size: SizeBytes) {
+        self.size += size.0;
+        if let Some((first, rest)) = path.split_first() {
+            self.children
+                .entry(first.clone())
+                .or_default()
+                .insert(rest, size);
+        }
+    }
+
+    fn into_tree(self, name: String) -> SizeTree {
+        let mut children: Vec<_> = self
+            .children
+            .into_iter()
+            .map(|(name, directory)| directory.into_tree(name))
+            .collect();
+        children.sort_by(|a, b| b.size.0.total_cmp(&a.size.0));
+
+        SizeTree {
+            name,
+            size: SizeBytes(self.size),
+            children,
+        }
+    }
+}
+
+/// Split a module name into the segments of the tree. The package is a
+/// single segment
+fn tree_path(name: &str) -> Vec<String> {
+    let resource = name.rsplit('!').next().unwrap_or(name).replace('\\', "/");
+    let (mut segments, rest) = match PackagePath::parse(&resource) {
+        Some(package) => (
+            vec![package.install_path.trim_start_matches("./").to_string()],
+            &resource[package.install_path.len()..],
+        ),
+        None => (vec![], resource.as_str()),
+    };
+
+    segments.extend(
+        rest.split('/')
+            .filter(|segment| !segment.is_empty() && *segment != ".")
+            .map(ToString::to_string),
+    );
+    segments
+}
+
+/// The modules a [`SizeTree`] is built from
+#[derive(Debug, Clone)]
+pub enum TreeScope<'a> {
+    /// Every module emitted in a chunk
+    Compilation,
+    Chunk(ChunkId),
+    /// Modules of the chunks an entrypoint loads. Asynchronous chunks are
+    /// left out when `initial_only` is set
+    Entrypoint {
+        chunks: &'a [ChunkId],
+        initial_only: bool,
+    },
+}
+
+/// Aggregate the size of every module in `scope`
+pub fn size_tree<M, Mv, C, Cv>(name: &str, scope: TreeScope, modules: &M, chunks: &C) -> SizeTree
+where
+    M: Modules<Mv>,
+    Mv: Module,
+    C: Chunks<Cv>,
+    Cv: Chunk,
+{
+    let chunks: Option<HashSet<ChunkId>> = match scope {
+        TreeScope::Compilation => None,
+        TreeScope::Chunk(chunk) => Some(HashSet::from([chunk])),
+        TreeScope::Entrypoint {
+            chunks: entry_chunks,
+            initial_only,
+        } => {
+            let graph = ChunkLoadGraph::build_graph(chunks);
+            if initial_only {
+                Some(initial_chunks(&graph, entry_chunks))
+            } else {
+                Some(reachable_chunks(&graph, entry_chunks))
+            }
+        }
+    };
+
+    // Concatenated modules are expanded below, and are also listed as top
+    // level modules without chunks when they are used elsewhere
+    let mut root = Directory::default();
+    for module in modules.top_level() {
+        let module_chunks: ModuleChunks = module.extract_data();
+        let in_scope = match &chunks {
+            None => !module_chunks.is_empty(),
+            Some(chunks) => !module_chunks.is_disjoint(chunks),
+        };
+        if !in_scope {
+            continue;
+        }
+
+        let mut stack = vec![module];
+        while let Some(module) = stack.pop() {
+            let concatenated = module.concatenated();
+            if concatenated.is_empty() {
+                root.insert(&tree_path(&module.label().0), module.extract_data());
+            } else {
+                stack.extend(concatenated);
+            }
+        }
+    }
+
+    root.into_tree(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webpack_stats::v5::Stats;
+
+    static SOURCE_FILE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../webpack-stats/test_projects/v5/compilation-stats.json"
+    ));
+
+    #[test]
+    fn root_size_counts_every_emitted_module_once() {
+        let stats: Stats = serde_json::from_str(SOURCE_FILE).unwrap();
+        let tree = |scope| size_tree("root", scope, &stats.modules, &stats.chunks).size;
+
+        // Runtime modules are emitted in both entry chunks and count twice
+        assert_eq!(tree(TreeScope::Compilation), SizeBytes(19012.0));
+        assert_eq!(
+            tree(TreeScope::Chunk(ChunkId::from(826))),
+            SizeBytes(10741.0)
+        );
+        assert_eq!(tree(TreeScope::Chunk(ChunkId::from(960))), SizeBytes(741.0));
+    }
+
+    #[test]
+    fn packages_are_a_single_segment() {
+        assert_eq!(
+            tree_path("./node_modules/@babel/runtime/helpers/esm/extends.js"),
+            vec![
+                "node_modules/@babel/runtime",
+                "helpers",
+                "esm",
+                "extends.js"
+            ]
+        );
+        assert_eq!(
+            tree_path("./node_modules/a/node_modules/lodash/map.js"),
+            vec!["node_modules/a/node_modules/lodash", "map.js"]
+        );
+        assert_eq!(
+            tree_path("./node_modules/babel-loader/lib/index.js!./src/app/index.js"),
+            vec!["src", "app", "index.js"]
+        );
+        assert_eq!(
+            tree_path("webpack/runtime/load script"),
+            vec!["webpack", "runtime", "load script"]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use meshed::prelude::*;
+use webpack_stats::chunk::ChunkId;
+use webpack_stats::v5::chunk::{Chunk, Chunks};
+
+static SOURCE_FILE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/test_projects/v5/compilation-stats.json"
+));
+
+/// What `Query::query` used to do before chunks were indexed
+fn linear_query<'c, 'a>(chunks: &'c Chunks<'a>, identifier: &ChunkId) -> Option<&'c Chunk<'a>> {
+    chunks.iter().find(|chunk| &chunk.get_id() == identifier)
+}
+
+/// The chunks of the v5 fixture, repeated with new ids until there are `count`
+fn synthetic_chunks(count: u32) -> String {
+    let stats: serde_json::Value = serde_json::from_str(SOURCE_FILE).unwrap();
+    let template = stats["chunks"].as_array().unwrap();
+    let chunks = (0..count)
+        .map(|id| {
+            let mut chunk = template[id as usize % template.len()].clone();
+            chunk["id"] = id.into();
+            chunk
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string(&chunks).unwrap()
+}
+
+fn query_every_chunk(c: &mut Criterion) {
+    let stats: webpack_stats::v5::Stats = serde_json::from_str(SOURCE_FILE).unwrap();
+    let large_source = synthetic_chunks(9_000);
+    let large: Chunks = serde_json::from_str(&large_source).unwrap();
+
+    let mut group = c.benchmark_group("query_every_chunk");
+    for (name, chunks) in [("v5_fixture", &stats.chunks), ("9000_chunks", &large)] {
+        let ids = chunks.iter().map(|c| c.id.clone()).collect::<Vec<_>>();
+
+        group.bench_with_input(BenchmarkId::new("indexed", name), &ids, |b, ids| {
+            b.iter(|| {
+                for id in ids {
+                    black_box(chunks.query(id));
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("linear", name), &ids, |b, ids| {
+            b.iter(|| {
+                for id in ids {
+                    black_box(linear_query(chunks, id));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = query_every_chunk
+}
+criterion_main!(benches);
@@ -14,13 +14,16 @@
  * limitations under the License.
  */
 
+pub mod asset;
 pub mod chunk;
+pub mod compilation;
+pub mod diagnostic;
 pub mod entry;
 pub mod import;
 pub mod module;
 
 use serde::de::{Error, SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::borrow::Cow;
 use std::fmt::{Debug, Display, Formatter};
 use std::marker::PhantomData;
@@ -41,7 +44,7 @@ impl<'de> Deserialize<'de> for DurationMillis {
 }
 
 /// Represents a size in bytes.
-#[derive(Default, Deserialize, Copy, Clone, PartialOrd, PartialEq)]
+#[derive(Default, Deserialize, Serialize, Copy, Clone, PartialOrd, PartialEq)]
 #[repr(transparent)]
 pub struct SizeBytes(pub f32);
 
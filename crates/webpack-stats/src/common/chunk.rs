@@ -15,38 +15,122 @@
  */
 
 use crate::module::ModuleIdentifier;
+use crate::rc::RefCount;
 use crate::SizeBytes;
 use meshed::prelude::*;
-use serde::{Deserialize, Serialize};
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::fmt::{Display, Formatter};
-use zerovec::ule::AsULE;
+use std::str::FromStr;
 
 #[derive(Deserialize, Serialize, Debug, Clone, Hash, PartialOrd, PartialEq, Eq)]
 #[serde(transparent)]
 pub struct ChunkName<'a>(#[serde(borrow)] Cow<'a, str>);
 
-#[derive(Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Copy, Clone)]
-#[repr(transparent)]
-pub struct ChunkId(pub u32);
+/// Chunk ids are numeric by default but become strings when
+/// `optimization.chunkIds: 'named'` is used. Strings in the stats file are
+/// always names, even when they look like numbers, so a chunk named "123" is
+/// not mistaken for chunk 123. Ids typed by a user go through [`FromStr`],
+/// which reads numbers, and [`Chunks::resolve_id`].
+#[derive(Serialize, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Clone)]
+#[serde(untagged)]
+pub enum ChunkId {
+    Number(u32),
+    Name(RefCount<str>),
+}
+
+impl From<u32> for ChunkId {
+    fn from(id: u32) -> Self {
+        Self::Number(id)
+    }
+}
+
+impl From<&str> for ChunkId {
+    fn from(id: &str) -> Self {
+        Self::Name(id.into())
+    }
+}
+
+/// For user input. Anything that looks like a number is a number
+impl FromStr for ChunkId {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.parse() {
+            Ok(number) => Self::Number(number),
+            Err(_) => Self::Name(s.into()),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ChunkId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visit;
+
+        impl<'v> Visitor<'v> for Visit {
+            type Value = ChunkId;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a numeric or string chunk id")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                u32::try_from(v)
+                    .map(ChunkId::Number)
+                    .map_err(|_| E::custom(format!("chunk id {} is out of range", v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                Ok(ChunkId::from(v))
+            }
+        }
+
+        deserializer.deserialize_any(Visit)
+    }
+}
 
 impl Display for ChunkId {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        Display::fmt(&self.0, f)
+        match self {
+            ChunkId::Number(id) => Display::fmt(id, f),
+            ChunkId::Name(name) => Display::fmt(name, f),
+        }
     }
 }
 
 impl Identity for ChunkId {}
 
-impl AsULE for ChunkId {
-    type ULE = <u32 as AsULE>::ULE;
+/// Position of every chunk in a list of chunks. Makes [`Query::query`] on
+/// chunk lists O(1) instead of a linear scan.
+#[derive(Debug, Default)]
+pub struct ChunkIndex(HashMap<ChunkId, usize>);
 
-    fn to_unaligned(self) -> Self::ULE {
-        u32::to_unaligned(self.0)
+impl ChunkIndex {
+    pub fn new<'c>(ids: impl IntoIterator<Item = &'c ChunkId>) -> Self {
+        Self(
+            ids.into_iter()
+                .enumerate()
+                .map(|(position, id)| (id.clone(), position))
+                .collect(),
+        )
     }
 
-    fn from_unaligned(unaligned: Self::ULE) -> Self {
-        Self(u32::from_unaligned(unaligned))
+    /// Find a chunk in the list the index was built from. The list must not
+    /// have been modified since.
+    pub fn find<'c, T>(&self, chunks: &'c [T], identifier: &ChunkId) -> Option<&'c T> {
+        self.0.get(identifier).and_then(|position| chunks.get(*position))
     }
 }
 
@@ -59,7 +143,8 @@ pub type ChunkModules = Vec<ModuleIdentifier>;
 #[derive(Debug)]
 pub struct ChunkInitial(pub bool);
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
 pub struct Files(pub Vec<String>);
 
 pub trait Chunk:
@@ -79,5 +164,24 @@ pub trait Chunk:
 pub trait Chunks<T>: Query<ChunkId, T>
 where
     T: Chunk,
+{
+    /// Ids parsed from user input are numbers when they look like one. Stats
+    /// files that spell numeric ids as strings, like rspack's, keep them as
+    /// names. Returns the spelling these chunks use
+    fn resolve_id(&self, id: ChunkId) -> ChunkId {
+        if let ChunkId::Number(number) = &id {
+            let name = ChunkId::Name(number.to_string().into());
+            if self.query(&id).is_none() && self.query(&name).is_some() {
+                return name;
+            }
+        }
+        id
+    }
+}
+
+impl<T, C> Chunks<T> for &C
+where
+    T: Chunk,
+    C: Chunks<T>,
 {
 }
@@ -0,0 +1,57 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/// A single webpack compilation. Multi-compiler builds (an array of configs)
+/// nest one child compilation per config under an otherwise empty parent.
+pub trait Compilation: Sized {
+    /// Value of the `name` option of the config that produced this compilation
+    fn name(&self) -> Option<&str>;
+    fn children(&self) -> &[Self];
+    fn into_children(self) -> Vec<Self>;
+
+    /// Names of every named compilation below this one, depth first
+    fn compilation_names(&self) -> Vec<&str> {
+        let mut names = vec![];
+        for child in self.children() {
+            if let Some(name) = child.name() {
+                names.push(name);
+            }
+            names.extend(child.compilation_names());
+        }
+        names
+    }
+
+    /// Find a compilation by name, searching this compilation and its
+    /// children depth first
+    fn compilation(&self, name: &str) -> Option<&Self> {
+        if self.name() == Some(name) {
+            return Some(self);
+        }
+        self.children()
+            .iter()
+            .find_map(|child| child.compilation(name))
+    }
+
+    /// Same as [`Compilation::compilation`] but takes ownership of the result
+    fn into_compilation(self, name: &str) -> Option<Self> {
+        if self.name() == Some(name) {
+            return Some(self);
+        }
+        self.into_children()
+            .into_iter()
+            .find_map(|child| child.into_compilation(name))
+    }
+}
@@ -0,0 +1,178 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::chunk::ChunkId;
+use crate::module::{ModuleId, ModuleIdentifier, ModuleName};
+use serde::{Deserialize, Deserializer};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+/// An error or a warning reported by the compilation.
+/// [webpack docs](https://webpack.js.org/api/stats/#errors-and-warnings)
+///
+/// Webpack 5 reports structured objects. Older versions only report a
+/// preformatted string, in which case only the message is filled in.
+#[derive(Debug, Default)]
+pub struct Diagnostic<'a> {
+    pub message: Cow<'a, str>,
+    /// Identifier of the module that caused the diagnostic
+    pub module_identifier: Option<ModuleIdentifier>,
+    pub module_name: Option<ModuleName>,
+    pub module_id: Option<ModuleId>,
+    /// Location in the module source. e.g. "1:0-20"
+    pub loc: Option<Cow<'a, str>>,
+    pub details: Option<Cow<'a, str>>,
+    /// Javascript stack of the webpack error. The first line starts with the
+    /// name of the error class.
+    pub stack: Option<Cow<'a, str>>,
+    pub chunk_id: Option<ChunkId>,
+    pub chunk_name: Option<Cow<'a, str>>,
+    /// Emitted asset the diagnostic is about. e.g. for performance warnings
+    pub file: Option<Cow<'a, str>>,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// Name of the webpack error class, e.g. `ModuleNotFoundError` or
+    /// `AssetsOverSizeLimitWarning`. Taken from the stack, so it is only
+    /// available when webpack reports one.
+    pub fn kind(&self) -> Option<&str> {
+        let first_line = self.stack.as_deref()?.lines().next()?;
+        let (kind, _) = first_line.split_once(':')?;
+        let kind = kind.trim();
+        if kind.is_empty() || kind.contains(char::is_whitespace) {
+            None
+        } else {
+            Some(kind)
+        }
+    }
+
+    /// The first line of the message
+    pub fn summary(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+impl<'a> Display for Diagnostic<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(kind) = self.kind() {
+            write!(f, "{}: ", kind)?;
+        }
+        write!(f, "{}", self.summary())?;
+        if let Some(loc) = &self.loc {
+            write!(f, " ({})", loc)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DiagnosticShape<'a> {
+    Message(#[serde(borrow)] Cow<'a, str>),
+    Object(#[serde(borrow)] DiagnosticObject<'a>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DiagnosticObject<'a> {
+    #[serde(borrow)]
+    message: Cow<'a, str>,
+    #[serde(default)]
+    module_identifier: Option<ModuleIdentifier>,
+    #[serde(default)]
+    module_name: Option<ModuleName>,
+    #[serde(default)]
+    module_id: Option<ModuleId>,
+    #[serde(borrow, default)]
+    loc: Option<Cow<'a, str>>,
+    #[serde(borrow, default)]
+    details: Option<Cow<'a, str>>,
+    #[serde(borrow, default)]
+    stack: Option<Cow<'a, str>>,
+    #[serde(default)]
+    chunk_id: Option<ChunkId>,
+    #[serde(borrow, default)]
+    chunk_name: Option<Cow<'a, str>>,
+    #[serde(borrow, default)]
+    file: Option<Cow<'a, str>>,
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Diagnostic<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let diagnostic = match DiagnosticShape::deserialize(deserializer)? {
+            DiagnosticShape::Message(message) => Diagnostic {
+                message,
+                ..Default::default()
+            },
+            DiagnosticShape::Object(object) => Diagnostic {
+                message: object.message,
+                module_identifier: object.module_identifier,
+                module_name: object.module_name,
+                module_id: object.module_id,
+                loc: object.loc,
+                details: object.details,
+                stack: object.stack,
+                chunk_id: object.chunk_id,
+                chunk_name: object.chunk_name,
+                file: object.file,
+            },
+        };
+
+        Ok(diagnostic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Diagnostic;
+    use crate::chunk::ChunkId;
+
+    #[test]
+    fn structured_diagnostic() {
+        let json = r#"{
+            "moduleIdentifier": "/app/src/index.js",
+            "moduleName": "./src/index.js",
+            "loc": "1:0-20",
+            "message": "Module not found: Error: Can't resolve './missing' in '/app/src'",
+            "moduleId": 826,
+            "moduleTrace": [],
+            "stack": "ModuleNotFoundError: Module not found: Error: Can't resolve './missing'\n    at ...",
+            "chunkId": 826,
+            "chunkName": "index"
+        }"#;
+        let diagnostic: Diagnostic = serde_json::from_str(json).unwrap();
+
+        assert_eq!(diagnostic.kind(), Some("ModuleNotFoundError"));
+        assert_eq!(diagnostic.chunk_id, Some(ChunkId::Number(826)));
+        assert_eq!(
+            diagnostic.module_identifier.unwrap().to_string(),
+            "/app/src/index.js"
+        );
+    }
+
+    #[test]
+    fn preformatted_diagnostic() {
+        let json = r#""./src/index.js\nModule not found: Error: Can't resolve './missing'""#;
+        let diagnostic: Diagnostic = serde_json::from_str(json).unwrap();
+
+        assert_eq!(diagnostic.kind(), None);
+        assert_eq!(diagnostic.summary(), "./src/index.js");
+        assert!(diagnostic.module_identifier.is_none());
+    }
+}
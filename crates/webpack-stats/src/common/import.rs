@@ -15,8 +15,12 @@
  */
 
 use crate::module::ModuleName;
+use crate::rc::RefCount;
+use serde::de::Visitor;
 use serde::{Deserialize, Deserializer};
 use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
 use std::path::Path;
 use std::str::FromStr;
 use thiserror::Error;
@@ -25,9 +29,53 @@ use thiserror::Error;
 #[serde(transparent)]
 pub struct SourceFilePath<'a>(#[serde(borrow)] pub Cow<'a, Path>);
 
-#[derive(Deserialize, Debug, Default)]
-#[serde(transparent)]
-pub struct SourceText<'a>(#[serde(borrow)] Cow<'a, str>);
+#[derive(Debug, Default)]
+pub struct SourceText<'a>(Cow<'a, str>);
+
+impl<'a> SourceText<'a> {
+    pub fn as_str(&self) -> &str {
+        self.0.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Source text is by far the largest part of a stats file. It is not read at
+/// all when [`LoadOptions::skip_source`](crate::load::LoadOptions) is set.
+impl<'de: 'a, 'a> Deserialize<'de> for SourceText<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visit<'a>(PhantomData<&'a str>);
+
+        impl<'v> Visitor<'v> for Visit<'v> {
+            type Value = SourceText<'v>;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("module source text")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(SourceText(Cow::Owned(v.to_owned())))
+            }
+
+            fn visit_borrowed_str<E>(self, v: &'v str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(SourceText(Cow::Borrowed(v)))
+            }
+        }
+
+        deserializer.deserialize_str(Visit(PhantomData))
+    }
+}
 
 #[derive(Debug, Copy, Clone)]
 pub enum ImportType {
@@ -77,13 +125,15 @@ impl FromStr for ImportType {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "require.context" => Ok(Self::RequireContext),
+            "require.context" | "context element" => Ok(Self::RequireContext),
             "import" => Ok(Self::Import),
-            "import()" => Ok(Self::ImportDynamic),
+            "import()" | "import() eager" | "import() weak" => Ok(Self::ImportDynamic),
+            // Webpack 4 and below only
+            "require.ensure" | "require.ensure item" | "System.import" => Ok(Self::ImportDynamic),
             "require" | "cjs require" | "cjs full require" => Ok(Self::Require),
-            "entry" => Ok(Self::Entry),
+            "entry" | "single entry" | "multi entry" => Ok(Self::Entry),
             "harmony side effect evaluation" => Ok(Self::Es6SideEffect),
-            "harmony import specifier" => Ok(Self::Import),
+            "harmony import specifier" | "harmony import" => Ok(Self::Import),
 
             "cjs self exports reference" => Ok(Self::CJSSelfExport),
             "cjs export require" => Ok(Self::CJSSelfExport),
@@ -114,3 +164,14 @@ impl<'de> Deserialize<'de> for ImportType {
 pub struct ImportString<'a>(Cow<'a, str>);
 
 pub struct ResolvedModule(pub ModuleName);
+
+/// Where the import is in the source of the importing module, e.g. `3:0-27`.
+/// Empty when webpack did not report a location
+#[derive(Debug, Clone, Default)]
+pub struct ImportLocation(pub RefCount<str>);
+
+impl Display for ImportLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
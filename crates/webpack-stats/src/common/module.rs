@@ -15,11 +15,12 @@
  */
 
 use crate::common::chunk::ChunkId;
-use crate::import::{ImportType, ResolvedModule};
+use crate::import::{ImportLocation, ImportType, ResolvedModule};
+use crate::SizeBytes;
 use meshed::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 
 use crate::rc::RefCount;
@@ -79,9 +80,68 @@ impl Clone for ModuleName {
     }
 }
 
-#[derive(Deserialize, Debug, Default)]
-#[serde(transparent)]
-pub struct ModuleId(u32);
+/// Webpack module ids are numeric by default but become strings when
+/// `NamedModulesPlugin` or `optimization.moduleIds: 'named'` is used.
+#[derive(Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ModuleId {
+    Number(u32),
+    Name(RefCount<str>),
+}
+
+impl Default for ModuleId {
+    fn default() -> Self {
+        Self::Number(0)
+    }
+}
+
+impl Display for ModuleId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModuleId::Number(id) => Display::fmt(id, f),
+            ModuleId::Name(name) => Display::fmt(name, f),
+        }
+    }
+}
+
+/// Position of every module in a list of modules. Concatenated modules link to
+/// the module they were concatenated into, like [`Link::Link`] in
+/// [`Query::create_index`].
+#[derive(Debug, Default)]
+pub struct ModuleIndex(HashMap<ModuleIdentifier, IndexEntry>);
+
+#[derive(Debug)]
+enum IndexEntry {
+    Position(usize),
+    Link(ModuleIdentifier),
+}
+
+impl ModuleIndex {
+    /// Register the module at `position`. Always wins over concatenated modules
+    pub fn insert_module(&mut self, identifier: &ModuleIdentifier, position: usize) {
+        self.0
+            .insert(identifier.clone(), IndexEntry::Position(position));
+    }
+
+    /// Register a module concatenated into the module `owner`
+    pub fn insert_concatenated(&mut self, identifier: &ModuleIdentifier, owner: &ModuleIdentifier) {
+        self.0
+            .entry(identifier.clone())
+            .or_insert_with(|| IndexEntry::Link(owner.clone()));
+    }
+
+    /// Find the module that owns `identifier` in the list the index was built
+    /// from. The list must not have been modified since.
+    pub fn find<'m, T>(&self, modules: &'m [T], identifier: &ModuleIdentifier) -> Option<&'m T> {
+        match self.0.get(identifier)? {
+            IndexEntry::Position(position) => modules.get(*position),
+            IndexEntry::Link(owner) => match self.0.get(owner)? {
+                IndexEntry::Position(position) => modules.get(*position),
+                IndexEntry::Link(_) => None,
+            },
+        }
+    }
+}
 
 #[derive(Deserialize, Debug, Default)]
 #[serde(transparent)]
@@ -91,12 +151,33 @@ pub trait Modules<T>: Query<ModuleIdentifier, T>
 where
     T: Module,
 {
+    /// The modules without the modules concatenated into them. Unlike
+    /// [`Query::all`] every byte of the compilation is counted once. Runtime
+    /// modules are listed once per chunk, with the same identifier.
+    fn top_level(&self) -> Vec<&T>;
+}
+
+impl<T, M> Modules<T> for &M
+where
+    T: Module,
+    M: Modules<T>,
+{
+    fn top_level(&self) -> Vec<&T> {
+        M::top_level(self)
+    }
 }
 pub trait Module:
     Identifiable<ModuleIdentifier>
-    + Edges<ModuleIdentifier, (ImportType, ResolvedModule)>
+    + Edges<ModuleIdentifier, (ImportType, ResolvedModule, ImportLocation)>
     + ExtractData<IncludedModuleNames>
     + ExtractData<ModuleChunks>
+    + ExtractData<SizeBytes>
     + Label<Label = ModuleName>
 {
+    /// The source text of the module, if the stats file was generated with
+    /// `stats.source` enabled.
+    fn source(&self) -> Option<&str>;
+
+    /// Modules that were concatenated into this one by scope hoisting
+    fn concatenated(&self) -> Vec<&Self>;
 }
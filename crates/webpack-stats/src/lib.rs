@@ -18,22 +18,78 @@ extern crate core;
 
 // # Webpack stats
 mod common;
+pub mod load;
 pub(crate) mod rc;
 
 pub use common::*;
+use compilation::Compilation;
 use std::borrow::Cow;
 
+#[cfg(feature = "rspack")]
+pub mod rspack;
+#[cfg(feature = "v2")]
+pub mod v2;
+#[cfg(feature = "v3")]
+pub mod v3;
+#[cfg(feature = "v4")]
+pub mod v4;
 #[cfg(feature = "v5")]
 pub mod v5;
 
 #[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Version<'a> {
     pub version: Cow<'a, str>,
+    /// Only present in stats files emitted by rspack
+    #[serde(default)]
+    pub rspack_version: Option<Cow<'a, str>>,
 }
 
 pub enum WebpackStats<'a> {
+    #[cfg(feature = "v2")]
+    V2(v2::Stats<'a>),
+    #[cfg(feature = "v3")]
+    V3(v3::Stats<'a>),
+    #[cfg(feature = "v4")]
+    V4(v4::Stats<'a>),
     #[cfg(feature = "v5")]
     V5(v5::Stats<'a>),
+    #[cfg(feature = "rspack")]
+    Rspack(rspack::Stats<'a>),
+}
+
+impl<'a> WebpackStats<'a> {
+    /// Names of the child compilations of a multi-compiler build
+    pub fn compilation_names(&self) -> Vec<&str> {
+        match self {
+            #[cfg(feature = "v2")]
+            WebpackStats::V2(stats) => stats.compilation_names(),
+            #[cfg(feature = "v3")]
+            WebpackStats::V3(stats) => stats.compilation_names(),
+            #[cfg(feature = "v4")]
+            WebpackStats::V4(stats) => stats.compilation_names(),
+            #[cfg(feature = "v5")]
+            WebpackStats::V5(stats) => stats.compilation_names(),
+            #[cfg(feature = "rspack")]
+            WebpackStats::Rspack(stats) => stats.compilation_names(),
+        }
+    }
+
+    /// Replace the stats with the (possibly nested) child compilation called `name`
+    pub fn into_compilation(self, name: &str) -> Option<Self> {
+        match self {
+            #[cfg(feature = "v2")]
+            WebpackStats::V2(stats) => stats.into_compilation(name).map(WebpackStats::V2),
+            #[cfg(feature = "v3")]
+            WebpackStats::V3(stats) => stats.into_compilation(name).map(WebpackStats::V3),
+            #[cfg(feature = "v4")]
+            WebpackStats::V4(stats) => stats.into_compilation(name).map(WebpackStats::V4),
+            #[cfg(feature = "v5")]
+            WebpackStats::V5(stats) => stats.into_compilation(name).map(WebpackStats::V5),
+            #[cfg(feature = "rspack")]
+            WebpackStats::Rspack(stats) => stats.into_compilation(name).map(WebpackStats::Rspack),
+        }
+    }
 }
 
 use thiserror::Error;
@@ -48,23 +104,6 @@ pub enum DeserializationError {
     UnsupportedVersion,
 }
 
-pub fn deserialize_any_version<'a>(
-    source: &'a str,
-) -> Result<WebpackStats<'a>, DeserializationError> {
-    // get a version
-    let version: Version<'a> = serde_json::from_str(source)
-        .map_err(|_| DeserializationError::VersionDeserializationError)?;
-
-    let version_major = version
-        .version
-        .trim()
-        .chars()
-        .next()
-        .ok_or(DeserializationError::VersionDeserializationError)?;
-
-    match version_major {
-        #[cfg(feature = "v5")]
-        '5' => Ok(WebpackStats::V5(serde_json::from_str(source)?)),
-        _ => Err(DeserializationError::UnsupportedVersion),
-    }
+pub fn deserialize_any_version(source: &str) -> Result<WebpackStats<'_>, DeserializationError> {
+    load::deserialize_any_version_with(source, load::LoadOptions::default())
 }
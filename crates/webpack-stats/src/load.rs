@@ -0,0 +1,358 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! # Loading large stats files
+//!
+//! Stats files of big projects easily reach gigabytes, most of it module
+//! source code. [`LoadOptions`] can drop the heaviest fields while
+//! deserializing and [`MappedFile`] (`mmap` feature) avoids reading the file
+//! into memory so the zero-copy types can borrow straight from the page cache.
+//! [`StatsFile`] picks the right way to load a file, including compressed ones.
+
+pub mod compression;
+mod skip;
+
+use crate::{DeserializationError, Version, WebpackStats};
+use compression::{decompress, Compression, DecompressionError};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Do not keep the source code of modules. `Module::source` is `None`
+    pub skip_source: bool,
+    /// Do not deserialize the emitted assets. `Stats::assets` is empty
+    pub skip_assets: bool,
+}
+
+/// Same as [`crate::deserialize_any_version`] but skips the fields disabled in
+/// `options`.
+pub fn deserialize_any_version_with(
+    source: &str,
+    options: LoadOptions,
+) -> Result<WebpackStats<'_>, DeserializationError> {
+    // get a version without parsing the whole file twice
+    let version = peek_version(source)?;
+
+    #[cfg(feature = "rspack")]
+    if version.rspack_version.is_some() {
+        return Ok(WebpackStats::Rspack(skip::from_str(source, options)?));
+    }
+
+    let version_major = version
+        .version
+        .trim()
+        .chars()
+        .next()
+        .ok_or(DeserializationError::VersionDeserializationError)?;
+
+    match version_major {
+        #[cfg(feature = "v2")]
+        '2' => Ok(WebpackStats::V2(skip::from_str(source, options)?)),
+        #[cfg(feature = "v3")]
+        '3' => Ok(WebpackStats::V3(skip::from_str(source, options)?)),
+        #[cfg(feature = "v4")]
+        '4' => Ok(WebpackStats::V4(skip::from_str(source, options)?)),
+        #[cfg(feature = "v5")]
+        '5' => Ok(WebpackStats::V5(skip::from_str(source, options)?)),
+        _ => Err(DeserializationError::UnsupportedVersion),
+    }
+}
+
+/// Find the version of the stats file without deserializing it. Only the top
+/// level object is looked at, nested values are skipped over byte by byte.
+/// Scanning stops once both `version` and `rspackVersion` are found, or at
+/// the end of the object. Rspack writes `rspackVersion` after `version`, so
+/// webpack files are scanned to the end, which skipping keeps cheap.
+pub fn peek_version(source: &str) -> Result<Version<'_>, DeserializationError> {
+    let mut scanner = Scanner {
+        source,
+        position: 0,
+    };
+
+    let mut version = None;
+    let mut rspack_version = None;
+
+    scanner.expect(b'{')?;
+    loop {
+        scanner.skip_whitespace();
+        if scanner.peek() == Some(b'}') {
+            break;
+        }
+        let key = scanner.string()?;
+        scanner.expect(b':')?;
+        match key.as_ref() {
+            "version" => version = Some(scanner.string()?),
+            "rspackVersion" => rspack_version = Some(scanner.string()?),
+            _ => scanner.skip_value()?,
+        }
+
+        if version.is_some() && rspack_version.is_some() {
+            break;
+        }
+
+        scanner.skip_whitespace();
+        match scanner.next() {
+            Some(b',') => continue,
+            Some(b'}') => break,
+            _ => return Err(DeserializationError::VersionDeserializationError),
+        }
+    }
+
+    Ok(Version {
+        version: version.ok_or(DeserializationError::VersionDeserializationError)?,
+        rspack_version,
+    })
+}
+
+struct Scanner<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.source.as_bytes().get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\n' | b'\r' | b'\t')) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), DeserializationError> {
+        self.skip_whitespace();
+        if self.next() == Some(byte) {
+            Ok(())
+        } else {
+            Err(DeserializationError::VersionDeserializationError)
+        }
+    }
+
+    /// Moves past the closing quote of a string. Returns true if the string
+    /// contained escapes.
+    fn skip_string(&mut self) -> Result<bool, DeserializationError> {
+        let mut escaped = false;
+        loop {
+            match self.next() {
+                Some(b'"') => return Ok(escaped),
+                Some(b'\\') => {
+                    escaped = true;
+                    self.position += 1;
+                }
+                Some(_) => {}
+                None => return Err(DeserializationError::VersionDeserializationError),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<Cow<'a, str>, DeserializationError> {
+        self.expect(b'"')?;
+        let start = self.position;
+        let escaped = self.skip_string()?;
+        let raw = &self.source[start..self.position - 1];
+        if escaped {
+            let quoted = &self.source[start - 1..self.position];
+            serde_json::from_str::<String>(quoted)
+                .map(Cow::Owned)
+                .map_err(|_| DeserializationError::VersionDeserializationError)
+        } else {
+            Ok(Cow::Borrowed(raw))
+        }
+    }
+
+    fn skip_value(&mut self) -> Result<(), DeserializationError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'"') => {
+                self.position += 1;
+                self.skip_string()?;
+            }
+            Some(b'{' | b'[') => {
+                let mut depth = 0usize;
+                loop {
+                    match self.next() {
+                        Some(b'{' | b'[') => depth += 1,
+                        Some(b'}' | b']') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        Some(b'"') => {
+                            self.skip_string()?;
+                        }
+                        Some(_) => {}
+                        None => return Err(DeserializationError::VersionDeserializationError),
+                    }
+                }
+            }
+            Some(_) => {
+                while !matches!(
+                    self.peek(),
+                    None | Some(b',' | b'}' | b']' | b' ' | b'\n' | b'\r' | b'\t')
+                ) {
+                    self.position += 1;
+                }
+            }
+            None => return Err(DeserializationError::VersionDeserializationError),
+        }
+        Ok(())
+    }
+}
+
+/// A read only memory map of a stats file
+#[cfg(feature = "mmap")]
+pub struct MappedFile {
+    map: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedFile {
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: the map is read only. Modifying the stats file while it is
+        // mapped is undefined behaviour, same as for any other mmap user.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Self { map })
+    }
+
+    pub fn as_str(&self) -> Result<&str, std::str::Utf8Error> {
+        std::str::from_utf8(&self.map)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StatsFileError {
+    #[error("Could not read stats file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Decompression(#[from] DecompressionError),
+}
+
+/// Contents of a stats file on disk. Plain json files are memory mapped when
+/// the `mmap` feature is enabled, compressed files are decompressed into memory.
+pub enum StatsFile {
+    #[cfg(feature = "mmap")]
+    Mapped(MappedFile),
+    Buffered(Vec<u8>),
+}
+
+impl StatsFile {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StatsFileError> {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+
+        let mut header = Vec::with_capacity(4);
+        (&mut file).take(4).read_to_end(&mut header)?;
+        let compression = Compression::detect(path, &header);
+
+        #[cfg(feature = "mmap")]
+        if compression == Compression::None {
+            return Ok(StatsFile::Mapped(MappedFile::open(path)?));
+        }
+
+        file.seek(SeekFrom::Start(0))?;
+        let contents = decompress(compression, BufReader::new(file))?;
+        Ok(StatsFile::Buffered(contents))
+    }
+
+    pub fn as_str(&self) -> Result<&str, std::str::Utf8Error> {
+        match self {
+            #[cfg(feature = "mmap")]
+            StatsFile::Mapped(map) => map.as_str(),
+            StatsFile::Buffered(contents) => std::str::from_utf8(contents),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deserialize_any_version_with, peek_version, LoadOptions};
+    use crate::WebpackStats;
+
+    #[test]
+    fn peek_version_skips_nested_values() {
+        let json = r#"{ "hash": "a\"b", "nested": { "version": "1", "list": [1, {"a": "}"}] },
+            "count": 12, "rspackVersion": "0.7.5", "version": "5.75.0" }"#;
+        let version = peek_version(json).unwrap();
+        assert_eq!(version.version, "5.75.0");
+        assert_eq!(version.rspack_version.as_deref(), Some("0.7.5"));
+
+        let version = peek_version(r#"{"version": "4.46.0", "children": []}"#).unwrap();
+        assert_eq!(version.version, "4.46.0");
+        assert!(version.rspack_version.is_none());
+
+        // Rspack writes its version after the webpack version
+        let version = peek_version(
+            r#"{"version": "5.75.0", "modules": [{"version": "1"}], "rspackVersion": "0.7.5"}"#,
+        )
+        .unwrap();
+        assert_eq!(version.version, "5.75.0");
+        assert_eq!(version.rspack_version.as_deref(), Some("0.7.5"));
+
+        // Nothing after both versions is looked at
+        let version = peek_version(r#"{"version": "5.75.0", "rspackVersion": "0.7.5", ["#).unwrap();
+        assert_eq!(version.rspack_version.as_deref(), Some("0.7.5"));
+
+        assert!(peek_version(r#"{"hash": "a"}"#).is_err());
+    }
+
+    #[test]
+    fn skip_heavy_fields() {
+        let source = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test_projects/v5/compilation-stats.json"
+        ))
+        .replace(
+            r#""built": true,"#,
+            r#""built": true, "source": "export {};\n","#,
+        );
+        let source = source.as_str();
+        let options = LoadOptions {
+            skip_source: true,
+            skip_assets: true,
+        };
+
+        match deserialize_any_version_with(source, options).unwrap() {
+            WebpackStats::V5(stats) => {
+                assert!(stats.assets.is_empty());
+                assert!(stats.modules.iter().all(|module| module.source.is_none()));
+                assert!(!stats.chunks.is_empty());
+            }
+            _ => panic!("Expected v5 stats"),
+        }
+
+        match crate::deserialize_any_version(source).unwrap() {
+            WebpackStats::V5(stats) => {
+                assert!(!stats.assets.is_empty());
+                let module = stats.modules.iter().next().unwrap();
+                assert_eq!(module.source.as_ref().unwrap().as_str(), "export {};\n");
+            }
+            _ => panic!("Expected v5 stats"),
+        }
+    }
+}
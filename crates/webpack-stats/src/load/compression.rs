@@ -0,0 +1,170 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Archived stats files are usually compressed. The compression is detected
+//! from the magic bytes of the file, brotli has none so the `.br` extension
+//! is used instead.
+
+use std::io::Read;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Brotli,
+    Zstd,
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+impl Compression {
+    /// `header` is the first few bytes of the file. Four bytes are enough.
+    pub fn detect(path: &Path, header: &[u8]) -> Self {
+        if header.starts_with(&GZIP_MAGIC) {
+            return Compression::Gzip;
+        }
+        if header.starts_with(&ZSTD_MAGIC) {
+            return Compression::Zstd;
+        }
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("br") => Compression::Brotli,
+            Some("zst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    fn feature(&self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Gzip => "gzip",
+            Compression::Brotli => "brotli",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DecompressionError {
+    #[error("Stats file is {0:?} compressed but webpack-stats was built without the `{feature}` feature", feature = .0.feature())]
+    UnsupportedCompression(Compression),
+    #[error("Could not decompress stats file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Decompress the whole of `reader` into memory
+pub fn decompress(
+    compression: Compression,
+    reader: impl Read,
+) -> Result<Vec<u8>, DecompressionError> {
+    let mut buffer = vec![];
+    match compression {
+        Compression::None => {
+            let mut reader = reader;
+            reader.read_to_end(&mut buffer)?;
+        }
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => {
+            flate2::read::MultiGzDecoder::new(reader).read_to_end(&mut buffer)?;
+        }
+        #[cfg(feature = "brotli")]
+        Compression::Brotli => {
+            brotli::Decompressor::new(reader, 4096).read_to_end(&mut buffer)?;
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            zstd::stream::read::Decoder::new(reader)?.read_to_end(&mut buffer)?;
+        }
+        #[allow(unreachable_patterns)]
+        unsupported => return Err(DecompressionError::UnsupportedCompression(unsupported)),
+    }
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Compression;
+    use std::path::Path;
+
+    #[cfg(any(feature = "gzip", feature = "brotli", feature = "zstd"))]
+    static SOURCE_FILE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test_projects/v5/compilation-stats.json"
+    ));
+
+    #[test]
+    fn detection() {
+        let json = Path::new("stats.json");
+        assert_eq!(Compression::detect(json, b"{\n  \""), Compression::None);
+        assert_eq!(
+            Compression::detect(json, &[0x1f, 0x8b, 0x08, 0x00]),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::detect(json, &[0x28, 0xb5, 0x2f, 0xfd]),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::detect(Path::new("stats.json.br"), &[0x1b, 0x00]),
+            Compression::Brotli
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip() {
+        use super::decompress;
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(SOURCE_FILE.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let decompressed = decompress(Compression::Gzip, compressed.as_slice()).unwrap();
+        assert_eq!(decompressed, SOURCE_FILE.as_bytes());
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn brotli() {
+        use super::decompress;
+        use std::io::Write;
+
+        let mut compressed = vec![];
+        {
+            let mut encoder = brotli::CompressorWriter::new(&mut compressed, 4096, 5, 22);
+            encoder.write_all(SOURCE_FILE.as_bytes()).unwrap();
+        }
+
+        let decompressed = decompress(Compression::Brotli, compressed.as_slice()).unwrap();
+        assert_eq!(decompressed, SOURCE_FILE.as_bytes());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd() {
+        use super::decompress;
+
+        let compressed = zstd::encode_all(SOURCE_FILE.as_bytes(), 3).unwrap();
+
+        let decompressed = decompress(Compression::Zstd, compressed.as_slice()).unwrap();
+        assert_eq!(decompressed, SOURCE_FILE.as_bytes());
+    }
+}
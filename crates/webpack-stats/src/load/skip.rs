@@ -0,0 +1,503 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Drop the fields disabled in [`LoadOptions`] while deserializing.
+//!
+//! Serde has no way to hand state to nested `Deserialize` impls, so the
+//! options travel with the deserializer instead. [`Skipping`] wraps the json
+//! deserializer along the few paths that lead to skippable fields, i.e.
+//! compilations, their chunks and their (concatenated) modules, and hands
+//! every other value straight to serde_json. A skipped value is read as
+//! [`IgnoredAny`] and the field sees an empty value.
+
+use serde::de::value::{
+    BorrowedStrDeserializer, MapDeserializer, SeqDeserializer, StringDeserializer,
+};
+use serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, IgnoredAny, MapAccess, SeqAccess, Visitor,
+};
+use serde::{forward_to_deserialize_any, Deserialize};
+use std::borrow::Cow;
+use std::fmt::Formatter;
+use std::marker::PhantomData;
+
+use super::LoadOptions;
+
+/// Deserialize a stats file of type `T`, skipping the fields disabled in
+/// `options`
+pub(crate) fn from_str<'a, T>(source: &'a str, options: LoadOptions) -> serde_json::Result<T>
+where
+    T: Deserialize<'a>,
+{
+    if options == LoadOptions::default() {
+        return serde_json::from_str(source);
+    }
+
+    let mut deserializer = serde_json::Deserializer::from_str(source);
+    let value = T::deserialize(Skipping {
+        inner: &mut deserializer,
+        context: Context::Compilation,
+        options,
+    })?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// The kind of json value being deserialized, as far as skipping cares
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Context {
+    Compilation,
+    Compilations,
+    Chunk,
+    Chunks,
+    Module,
+    Modules,
+}
+
+/// What to do with the value of a map entry
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Action {
+    Pass,
+    Wrap(Context),
+    Skip,
+}
+
+impl Context {
+    fn entry(self, key: &str, options: LoadOptions) -> Action {
+        match (self, key) {
+            (Context::Compilation, "assets") if options.skip_assets => Action::Skip,
+            (Context::Compilation, "children") => Action::Wrap(Context::Compilations),
+            (Context::Compilation, "chunks") => Action::Wrap(Context::Chunks),
+            (Context::Module, "source") if options.skip_source => Action::Skip,
+            (Context::Compilation | Context::Chunk | Context::Module, "modules") => {
+                Action::Wrap(Context::Modules)
+            }
+            _ => Action::Pass,
+        }
+    }
+
+    fn element(self) -> Option<Context> {
+        match self {
+            Context::Compilations => Some(Context::Compilation),
+            Context::Chunks => Some(Context::Chunk),
+            Context::Modules => Some(Context::Module),
+            _ => None,
+        }
+    }
+}
+
+struct Skipping<D> {
+    inner: D,
+    context: Context,
+    options: LoadOptions,
+}
+
+impl<D> Skipping<D> {
+    fn visitor<V>(&self, visitor: V) -> SkippingVisitor<V> {
+        SkippingVisitor {
+            inner: visitor,
+            context: self.context,
+            options: self.options,
+        }
+    }
+}
+
+/// Forward every `deserialize_*` method to the wrapped deserializer with a
+/// wrapped visitor
+macro_rules! forward_deserialize {
+    ($wrap:ident; $($method:ident($($arg:ident: $ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let visitor = self.$wrap(visitor);
+                self.inner.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+macro_rules! deserializer_methods {
+    ($wrap:ident) => {
+        forward_deserialize! {
+            $wrap;
+            deserialize_any(),
+            deserialize_bool(),
+            deserialize_i8(),
+            deserialize_i16(),
+            deserialize_i32(),
+            deserialize_i64(),
+            deserialize_u8(),
+            deserialize_u16(),
+            deserialize_u32(),
+            deserialize_u64(),
+            deserialize_f32(),
+            deserialize_f64(),
+            deserialize_char(),
+            deserialize_str(),
+            deserialize_string(),
+            deserialize_bytes(),
+            deserialize_byte_buf(),
+            deserialize_option(),
+            deserialize_unit(),
+            deserialize_unit_struct(name: &'static str),
+            deserialize_newtype_struct(name: &'static str),
+            deserialize_seq(),
+            deserialize_tuple(len: usize),
+            deserialize_tuple_struct(name: &'static str, len: usize),
+            deserialize_map(),
+            deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+            deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+            deserialize_identifier(),
+            deserialize_ignored_any(),
+        }
+    };
+}
+
+impl<'de, D> Deserializer<'de> for Skipping<D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    deserializer_methods!(visitor);
+}
+
+struct SkippingVisitor<V> {
+    inner: V,
+    context: Context,
+    options: LoadOptions,
+}
+
+/// Forward scalar `visit_*` methods to the wrapped visitor
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method<E>(self, v: $ty) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V> Visitor<'de> for SkippingVisitor<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(bool),
+        visit_i64(i64),
+        visit_u64(u64),
+        visit_f64(f64),
+        visit_char(char),
+        visit_str(&str),
+        visit_borrowed_str(&'de str),
+        visit_string(String),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(Vec<u8>),
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner.visit_some(Skipping {
+            inner: deserializer,
+            context: self.context,
+            options: self.options,
+        })
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner.visit_newtype_struct(Skipping {
+            inner: deserializer,
+            context: self.context,
+            options: self.options,
+        })
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        match self.context.element() {
+            Some(context) => self.inner.visit_seq(SkippingSeq {
+                inner: seq,
+                context,
+                options: self.options,
+            }),
+            None => self.inner.visit_seq(seq),
+        }
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.inner.visit_map(SkippingMap {
+            inner: map,
+            context: self.context,
+            options: self.options,
+            action: Action::Pass,
+        })
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        self.inner.visit_enum(data)
+    }
+}
+
+struct SkippingSeed<S> {
+    inner: S,
+    context: Context,
+    options: LoadOptions,
+}
+
+impl<'de, S> DeserializeSeed<'de> for SkippingSeed<S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = S::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner.deserialize(Skipping {
+            inner: deserializer,
+            context: self.context,
+            options: self.options,
+        })
+    }
+}
+
+struct SkippingSeq<A> {
+    inner: A,
+    context: Context,
+    options: LoadOptions,
+}
+
+impl<'de, A> SeqAccess<'de> for SkippingSeq<A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner.next_element_seed(SkippingSeed {
+            inner: seed,
+            context: self.context,
+            options: self.options,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+struct SkippingMap<A> {
+    inner: A,
+    context: Context,
+    options: LoadOptions,
+    /// Decided when the key is read, applied to the value
+    action: Action,
+}
+
+impl<'de, A> MapAccess<'de> for SkippingMap<A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.action = Action::Pass;
+        self.inner.next_key_seed(KeySeed {
+            inner: seed,
+            context: self.context,
+            options: self.options,
+            action: &mut self.action,
+        })
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.action {
+            Action::Pass => self.inner.next_value_seed(seed),
+            Action::Wrap(context) => self.inner.next_value_seed(SkippingSeed {
+                inner: seed,
+                context,
+                options: self.options,
+            }),
+            Action::Skip => {
+                self.inner.next_value::<IgnoredAny>()?;
+                seed.deserialize(Skipped(PhantomData))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+/// Reads a map key and records what to do with its value
+struct KeySeed<'s, S> {
+    inner: S,
+    context: Context,
+    options: LoadOptions,
+    action: &'s mut Action,
+}
+
+impl<'de, 's, S> DeserializeSeed<'de> for KeySeed<'s, S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = S::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let key = deserializer.deserialize_str(KeyVisitor)?;
+        *self.action = self.context.entry(&key, self.options);
+        match key {
+            Cow::Borrowed(key) => self.inner.deserialize(BorrowedStrDeserializer::new(key)),
+            Cow::Owned(key) => self.inner.deserialize(StringDeserializer::new(key)),
+        }
+    }
+}
+
+/// Keys without escapes are borrowed from the source
+struct KeyVisitor;
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = Cow<'de, str>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a map key")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Cow::Owned(v.to_owned()))
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Cow::Borrowed(v))
+    }
+}
+
+/// Stands in for a skipped value. Options become `None`, everything else
+/// an empty sequence or map.
+struct Skipped<E>(PhantomData<E>);
+
+impl<'de, E> Deserializer<'de> for Skipped<E>
+where
+    E: serde::de::Error,
+{
+    type Error = E;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_none()
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(SeqDeserializer::new(std::iter::empty::<()>()))
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(MapDeserializer::new(std::iter::empty::<((), ())>()))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct tuple
+        tuple_struct enum identifier ignored_any
+    }
+}
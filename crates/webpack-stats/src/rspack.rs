@@ -0,0 +1,102 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! # Rspack Stats
+//!
+//! [Rspack](https://www.rspack.dev/) emits a stats file that is close to
+//! webpack 5. Chunk and module ids are strings, `profile` is never reported
+//! and some module and chunk fields are left out. Everything but the stats
+//! root shares the webpack 5 representation, which defaults the missing
+//! fields.
+
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::common::chunk::ChunkName;
+use crate::common::compilation::Compilation;
+use crate::common::diagnostic::Diagnostic;
+use crate::v5::asset::Asset;
+use crate::v5::emit::AssetPath;
+use crate::v5::entry_point::EntryPoint;
+
+use crate::rspack::chunk::Chunks;
+use crate::rspack::module::Modules;
+use crate::DurationMillis;
+
+/// Rspack chunks, modules and reasons have the webpack 5 shape. The fields
+/// rspack leaves out are defaulted
+pub use crate::v5::{chunk, module, reason};
+
+/// # Rspack stats file
+///
+/// Deserialized representation of the rspack stats file. Will
+/// try to borrow as much as it can from the underlying buffer.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Stats<'a> {
+    /// Version of rspack used for the compilation
+    pub rspack_version: Cow<'a, str>,
+    /// Webpack version that rspack claims compatibility with (5.x.x)
+    pub version: Cow<'a, str>,
+    /// Set through the `name` option of the config. Usually only present
+    /// on child compilations.
+    pub name: Option<Cow<'a, str>>,
+    /// Compilation specific hash
+    pub hash: Cow<'a, str>,
+    /// Compilation time in milliseconds
+    pub time: DurationMillis,
+    /// Undocumented by rspack
+    pub public_path: Cow<'a, str>,
+    /// path to rspack output directory
+    pub output_path: Cow<'a, str>,
+    /// Chunk name to emitted asset(s) mapping
+    #[serde(borrow)]
+    pub assets_by_chunk_name: ChunkMapping<'a>,
+    pub entrypoints: HashMap<Cow<'a, str>, EntryPoint<'a>>,
+    /// Empty when loaded with [`LoadOptions::skip_assets`](crate::load::LoadOptions)
+    pub assets: Vec<Asset<'a>>,
+    pub chunks: Chunks<'a>,
+    pub modules: Modules<'a>,
+
+    #[serde(borrow)]
+    pub errors: Vec<Diagnostic<'a>>,
+    pub errors_count: usize,
+    #[serde(borrow)]
+    pub warnings: Vec<Diagnostic<'a>>,
+    pub warnings_count: usize,
+    /// Child compilations, one per config in multi-compiler builds
+    pub children: Vec<Self>,
+}
+
+type ChunkMapping<'a> = HashMap<ChunkName<'a>, Vec<AssetPath<'a>>>;
+
+impl<'a> Compilation for Stats<'a> {
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn children(&self) -> &[Self] {
+        &self.children
+    }
+
+    fn into_children(self) -> Vec<Self> {
+        self.children
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,72 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::chunk::ChunkId;
+
+// Derived from the webpack 5 sample project. String chunk and module ids,
+// `rspackVersion` written after `version` and the omitted fields follow the
+// rspack stats output.
+static SOURCE_FILE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/test_projects/rspack/compilation-stats.json"
+));
+
+#[test]
+fn full_deserialization() {
+    let value: super::Stats = serde_json::from_str(SOURCE_FILE).expect("Does serde");
+
+    assert!(value
+        .chunks
+        .iter()
+        .any(|chunk| chunk.id == ChunkId::from("826")));
+    assert!(!value.modules.is_empty());
+}
+
+#[test]
+fn numeric_input_resolves_to_string_ids() {
+    use crate::chunk::Chunks as _;
+
+    let value: super::Stats = serde_json::from_str(SOURCE_FILE).expect("Does serde");
+    let input: ChunkId = "826".parse().unwrap();
+
+    assert_eq!(value.chunks.resolve_id(input), ChunkId::from("826"));
+    assert_eq!(
+        value.chunks.resolve_id(ChunkId::Number(4_000)),
+        ChunkId::Number(4_000)
+    );
+}
+
+#[test]
+fn any_version_detects_rspack() {
+    let value = crate::deserialize_any_version(SOURCE_FILE).expect("Does serde");
+    assert!(matches!(value, crate::WebpackStats::Rspack(_)));
+}
+
+#[test]
+fn chunks_without_modules_or_origin_modules() {
+    let value: super::Stats = serde_json::from_str(
+        r#"{ "chunks": [{
+            "id": "main", "entry": true, "initial": true, "files": ["main.js"],
+            "names": ["main"], "parents": [], "size": 10,
+            "origins": [{ "loc": "main", "moduleName": "./index.js" }]
+        }] }"#,
+    )
+    .expect("Does serde");
+
+    let chunk = value.chunks.iter().next().unwrap();
+    assert!(chunk.modules.is_empty());
+    assert!(chunk.origins[0].module_identifier.is_none());
+}
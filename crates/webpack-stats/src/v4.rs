@@ -0,0 +1,110 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! # Webpack V4 Stats
+//!
+//! As much of the webpack 4 stats file as described in
+//! [webpack 4 docs](https://v4.webpack.js.org/api/stats/)
+//!
+//! Webpack 2 and 3 write the same schema with a few fields missing, so the
+//! [`v2`](crate::v2) and [`v3`](crate::v3) modules re-export this one.
+//! Older webpack versions do not report chunk children or siblings. They
+//! are derived from the chunk parents during deserialization.
+//!
+
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::common::chunk::ChunkName;
+use crate::common::compilation::Compilation;
+use crate::common::diagnostic::Diagnostic;
+use crate::common::Strings;
+use asset::Asset;
+
+use crate::v4::chunk::Chunks;
+use crate::v4::module::Modules;
+use crate::DurationMillis;
+
+use crate::v4::entry_point::EntryPoint;
+
+pub mod asset;
+pub mod entry_point;
+/// Webpack 4 chunks, modules and reasons have the webpack 5 shape minus a
+/// few fields, which are defaulted
+pub use crate::v5::{chunk, module, reason};
+
+/// # Webpack stats file
+///
+/// Deserialized representation of the webpack v2, v3 and v4 stats file. Will
+/// try to borrow as much as it can from the underlying buffer.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Stats<'a> {
+    /// Version of webpack used for the compilation (2.x.x to 4.x.x)
+    pub version: Cow<'a, str>,
+    /// Set through the `name` option of the config. Usually only present
+    /// on child compilations.
+    pub name: Option<Cow<'a, str>>,
+    /// Compilation specific hash
+    pub hash: Cow<'a, str>,
+    /// Compilation time in milliseconds
+    pub time: DurationMillis,
+    /// Undocumented by webpack
+    pub public_path: Cow<'a, str>,
+    /// path to webpack output directory. Not reported by webpack 2 or 3
+    pub output_path: Cow<'a, str>,
+    /// Chunk name to emitted asset(s) mapping. Webpack 4 emits a plain
+    /// string when a chunk only has a single asset
+    #[serde(borrow)]
+    pub assets_by_chunk_name: ChunkMapping<'a>,
+    /// Entrypoints are keyed by name. Webpack 4 does not repeat the name
+    /// inside of the entrypoint object so it is filled in from the key.
+    #[serde(deserialize_with = "entry_point::deserialize_entrypoints")]
+    pub entrypoints: HashMap<Cow<'a, str>, EntryPoint<'a>>,
+    /// Empty when loaded with [`LoadOptions::skip_assets`](crate::load::LoadOptions)
+    pub assets: Vec<Asset<'a>>,
+    pub chunks: Chunks<'a>,
+    pub modules: Modules<'a>,
+
+    /// Webpack 2 to 4 report errors and warnings as preformatted strings.
+    /// Only the message of the diagnostics is filled in.
+    #[serde(borrow)]
+    pub errors: Vec<Diagnostic<'a>>,
+    #[serde(borrow)]
+    pub warnings: Vec<Diagnostic<'a>>,
+    /// Child compilations, one per config in multi-compiler builds
+    pub children: Vec<Self>,
+}
+
+type ChunkMapping<'a> = HashMap<ChunkName<'a>, Strings<'a, str>>;
+
+impl<'a> Compilation for Stats<'a> {
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn children(&self) -> &[Self] {
+        &self.children
+    }
+
+    fn into_children(self) -> Vec<Self> {
+        self.children
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,53 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::common::chunk::ChunkId;
+use crate::common::chunk::ChunkName;
+use std::borrow::Cow;
+
+use crate::common::SizeBytes;
+use serde::Deserialize;
+
+// # Assets
+// (Link to webpack docs)[https://v4.webpack.js.org/api/stats/#asset-objects]
+//
+// Each assets object represents an output file emitted from the compilation.
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Asset<'a> {
+    /// The `output` filename
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    /// The chunks this asset contains
+    pub chunk_names: Vec<ChunkName<'a>>,
+    /// The chunk IDs this asset contains
+    pub chunks: Vec<ChunkId>,
+    /// Indicates whether or not the asset made it to the `output` directory
+    pub emitted: bool,
+    /// The size of the file in bytes
+    pub size: SizeBytes,
+}
+
+impl<'a> crate::common::asset::Asset for Asset<'a> {
+    fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    fn size(&self) -> SizeBytes {
+        self.size
+    }
+}
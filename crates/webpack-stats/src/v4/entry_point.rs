@@ -0,0 +1,85 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::chunk::ChunkId;
+use serde::{Deserialize, Deserializer};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+#[derive(Default, Debug)]
+pub struct EntryPoint<'a> {
+    /// Not present in the stats file. Filled in from the entrypoint map key
+    pub name: Cow<'a, str>,
+    pub chunks: Cow<'a, [ChunkId]>,
+    pub assets: Vec<Cow<'a, str>>,
+}
+
+/// Webpack 3 and 4 report `{ chunks, assets }` for every entrypoint. Some
+/// webpack 2 releases only report the list of chunk ids.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EntryPointShape<'a> {
+    Object {
+        chunks: Cow<'a, [ChunkId]>,
+        #[serde(borrow, default)]
+        assets: Vec<Cow<'a, str>>,
+    },
+    Chunks(Cow<'a, [ChunkId]>),
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for EntryPoint<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (chunks, assets) = match EntryPointShape::deserialize(deserializer)? {
+            EntryPointShape::Object { chunks, assets } => (chunks, assets),
+            EntryPointShape::Chunks(chunks) => (chunks, vec![]),
+        };
+
+        Ok(EntryPoint {
+            name: Default::default(),
+            chunks,
+            assets,
+        })
+    }
+}
+
+impl<'a> crate::common::entry::Entrypoint for EntryPoint<'a> {
+    fn chunks(&self) -> &[ChunkId] {
+        self.chunks.as_ref()
+    }
+
+    fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+}
+
+pub(crate) fn deserialize_entrypoints<'de: 'a, 'a, D>(
+    deserializer: D,
+) -> Result<HashMap<Cow<'a, str>, EntryPoint<'a>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut entrypoints: HashMap<Cow<'a, str>, EntryPoint<'a>> =
+        Deserialize::deserialize(deserializer)?;
+
+    for (name, entrypoint) in entrypoints.iter_mut() {
+        entrypoint.name = name.clone();
+    }
+
+    Ok(entrypoints)
+}
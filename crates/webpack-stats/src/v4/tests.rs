@@ -0,0 +1,103 @@
+/*
+ * Copyright [2022] [Kevin Velasco]
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ * http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::chunk::ChunkId;
+use crate::entry::Entrypoint;
+
+static SOURCE_FILE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/test_projects/v4/compilation-stats.json"
+));
+
+#[test]
+fn full_deserialization() {
+    let value: super::Stats = serde_json::from_str(SOURCE_FILE).expect("Does serde");
+
+    assert_eq!(value.chunks.len(), 5);
+    for (name, entrypoint) in value.entrypoints.iter() {
+        assert_eq!(name.as_ref(), entrypoint.name());
+    }
+}
+
+#[test]
+fn any_version_detects_v4() {
+    let value = crate::deserialize_any_version(SOURCE_FILE).expect("Does serde");
+    assert!(matches!(value, crate::WebpackStats::V4(_)));
+}
+
+#[test]
+fn reasons_fall_back_to_the_importer_name() {
+    let value: super::Stats = serde_json::from_str(SOURCE_FILE).expect("Does serde");
+    let reasons: Vec<_> = value
+        .modules
+        .iter()
+        .flat_map(|module| module.reasons.iter())
+        .collect();
+
+    assert!(!reasons.is_empty());
+    for reason in reasons {
+        assert!(reason.resolved_module.0.is_empty());
+        assert_eq!(reason.resolved_module().0, reason.module_name.0);
+    }
+}
+
+static V2_SOURCE_FILE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/test_projects/v2/compilation-stats.json"
+));
+
+static V3_SOURCE_FILE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/test_projects/v3/compilation-stats.json"
+));
+
+#[test]
+fn legacy_full_deserialization() {
+    for source in [V2_SOURCE_FILE, V3_SOURCE_FILE] {
+        let value: super::Stats = serde_json::from_str(source).expect("Does serde");
+
+        let entrypoint = value.entrypoints.get("index").expect("Has index entry");
+        assert_eq!(entrypoint.name(), "index");
+        assert_eq!(entrypoint.chunks(), &[ChunkId::Number(2)]);
+
+        // Children are derived from the parents of the async chunks
+        let entry_chunk = value.chunks.iter().find(|c| c.id == ChunkId::Number(2));
+        let mut children = entry_chunk.unwrap().children.clone();
+        children.sort();
+        assert_eq!(children, vec![ChunkId::Number(0), ChunkId::Number(1)]);
+    }
+}
+
+#[test]
+fn entrypoint_chunk_list_shape() {
+    let value: super::Stats =
+        serde_json::from_str(r#"{ "entrypoints": { "main": [0, 1] } }"#).expect("Does serde");
+
+    let entrypoint = value.entrypoints.get("main").expect("Has main entry");
+    assert_eq!(
+        entrypoint.chunks(),
+        &[ChunkId::Number(0), ChunkId::Number(1)]
+    );
+}
+
+#[test]
+fn any_version_detects_legacy() {
+    let v2 = crate::deserialize_any_version(V2_SOURCE_FILE).expect("Does serde");
+    assert!(matches!(v2, crate::WebpackStats::V2(_)));
+
+    let v3 = crate::deserialize_any_version(V3_SOURCE_FILE).expect("Does serde");
+    assert!(matches!(v3, crate::WebpackStats::V3(_)));
+}
@@ -25,6 +25,8 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 
 use crate::common::chunk::ChunkName;
+use crate::common::compilation::Compilation;
+use crate::common::diagnostic::Diagnostic;
 use asset::Asset;
 
 use crate::v5::chunk::Chunks;
@@ -50,6 +52,9 @@ pub mod reason;
 pub struct Stats<'a> {
     /// Version of webpack used for the compilation (5.x.x)
     pub version: Cow<'a, str>,
+    /// Set through the `name` option of the config. Usually only present
+    /// on child compilations.
+    pub name: Option<Cow<'a, str>>,
     /// Compilation specific hash
     pub hash: Cow<'a, str>,
     /// Compilation time in milliseconds
@@ -62,22 +67,36 @@ pub struct Stats<'a> {
     #[serde(borrow)]
     pub assets_by_chunk_name: ChunkMapping<'a>,
     pub entrypoints: HashMap<Cow<'a, str>, EntryPoint<'a>>,
+    /// Empty when loaded with [`LoadOptions::skip_assets`](crate::load::LoadOptions)
     pub assets: Vec<Asset<'a>>,
     pub chunks: Chunks<'a>,
     pub modules: Modules<'a>,
     // pub entry_points: Vec<EntryPoint<'a>>
-
-    // TODO:
-    #[serde(skip)]
-    errors: Vec<()>,
-    errors_count: usize,
-    #[serde(skip)]
-    warnings: Vec<()>,
-    warnings_count: usize,
-    children: Vec<Self>,
+    #[serde(borrow)]
+    pub errors: Vec<Diagnostic<'a>>,
+    pub errors_count: usize,
+    #[serde(borrow)]
+    pub warnings: Vec<Diagnostic<'a>>,
+    pub warnings_count: usize,
+    /// Child compilations, one per config in multi-compiler builds
+    pub children: Vec<Self>,
 }
 
 type ChunkMapping<'a> = HashMap<ChunkName<'a>, Vec<AssetPath<'a>>>;
 
+impl<'a> Compilation for Stats<'a> {
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn children(&self) -> &[Self] {
+        &self.children
+    }
+
+    fn into_children(self) -> Vec<Self> {
+        self.children
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -17,7 +17,6 @@
 use crate::common::chunk::ChunkId;
 use crate::common::chunk::ChunkName;
 use std::borrow::Cow;
-use zerovec::ZeroVec;
 
 use crate::common::SizeBytes;
 use serde::Deserialize;
@@ -43,8 +42,7 @@ pub struct Asset<'a> {
     #[serde(skip)]
     pub chunk_id_hints: &'a [()],
     /// The chunk IDs this asset contains
-    #[serde(borrow)]
-    pub chunks: ZeroVec<'a, ChunkId>,
+    pub chunks: Vec<ChunkId>,
     /// Indicates whether or not the asset was compared with the same file on the output file system
     pub compared_for_emit: bool,
     /// The size of the file in bytes
@@ -67,3 +65,13 @@ pub struct AssetInfo<'a> {
     pub javascript_module: bool,
     pub minimized: bool,
 }
+
+impl<'a> crate::common::asset::Asset for Asset<'a> {
+    fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    fn size(&self) -> SizeBytes {
+        self.size
+    }
+}
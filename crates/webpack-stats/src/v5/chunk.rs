@@ -17,31 +17,79 @@
 use crate::common::SizeBytes;
 use crate::v5::module::Modules;
 use crate::v5::reason::Reasons;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use std::borrow::Cow;
+use std::collections::HashMap;
 
 use crate::chunk::{
     ChunkChild, ChunkChildren, ChunkInitial, ChunkModules, ChunkParentOrSibling, Files,
 };
-use crate::common::chunk::{ChunkId, ChunkName};
+use crate::common::chunk::{ChunkId, ChunkIndex, ChunkName};
 use crate::common::import::SourceFilePath;
 use crate::common::module::{ModuleId, ModuleIdentifier, RelativeModulePath};
 use meshed::prelude::*;
 
-#[derive(Debug, Default, Deserialize)]
-#[serde(transparent)]
+/// The chunks of a compilation. Read only so that the index always matches
+/// the chunks.
+#[derive(Debug, Default)]
 pub struct Chunks<'a> {
-    #[serde(borrow)]
-    pub chunks: Vec<Chunk<'a>>,
+    chunks: Vec<Chunk<'a>>,
+    index: ChunkIndex,
+}
+
+impl<'a> Chunks<'a> {
+    pub fn new(chunks: Vec<Chunk<'a>>) -> Self {
+        let index = ChunkIndex::new(chunks.iter().map(|chunk| &chunk.id));
+        Self { chunks, index }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Chunk<'a>> {
+        self.chunks.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Chunks<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut chunks: Vec<Chunk<'a>> = Deserialize::deserialize(deserializer)?;
+
+        // Webpack 2 and 3 only list the parents of a chunk. Derive the children
+        // so that chunk graphs can be walked top down like newer versions.
+        let mut children: HashMap<ChunkId, Vec<ChunkId>> = HashMap::new();
+        for chunk in chunks.iter() {
+            for parent in chunk.parents.iter() {
+                children
+                    .entry(parent.clone())
+                    .or_default()
+                    .push(chunk.id.clone());
+            }
+        }
+
+        for chunk in chunks.iter_mut() {
+            if chunk.children.is_empty() {
+                chunk.children = children.remove(&chunk.id).unwrap_or_default();
+            }
+        }
+
+        Ok(Self::new(chunks))
+    }
 }
 
 impl<'a> crate::common::chunk::Chunks<Chunk<'a>> for Chunks<'a> {}
 
 impl<'a> Query<ChunkId, Chunk<'a>> for Chunks<'a> {
     fn query(&self, identifier: &ChunkId) -> Option<&Chunk<'a>> {
-        self.chunks
-            .iter()
-            .find(|chunk| &chunk.get_id() == identifier)
+        self.index.find(&self.chunks, identifier)
     }
 
     fn all(&self) -> Vec<&Chunk<'a>> {
@@ -55,7 +103,8 @@ pub struct Chunk<'a> {
     pub id: ChunkId,
     pub entry: bool,
     pub initial: bool,
-    #[serde(borrow)]
+    /// Rspack only reports them when `stats.chunkModules` is enabled
+    #[serde(borrow, default)]
     pub modules: Modules<'a>,
     pub files: Vec<SourceFilePath<'a>>,
     #[serde(borrow)]
@@ -63,15 +112,21 @@ pub struct Chunk<'a> {
     #[serde(default)]
     pub origins: Vec<Origin<'a>>,
     pub parents: Vec<ChunkId>,
+    /// Not reported by webpack 2 and 3
+    #[serde(default)]
     pub siblings: Vec<ChunkId>,
+    /// Derived from the parents of every other chunk when not reported
+    #[serde(default)]
     pub children: Vec<ChunkId>,
+    /// Not reported by rspack
+    #[serde(default)]
     pub rendered: bool,
     pub size: SizeBytes,
 }
 
 impl<'a> ExtractData<ChunkId> for Chunk<'a> {
     fn extract_data(&self) -> ChunkId {
-        self.id
+        self.id.clone()
     }
 }
 
@@ -90,7 +145,6 @@ impl<'a> ExtractData<SizeBytes> for Chunk<'a> {
 impl<'a> ExtractData<ChunkModules> for Chunk<'a> {
     fn extract_data(&self) -> ChunkModules {
         self.modules
-            .modules
             .iter()
             .map(|module| module.identifier.clone())
             .collect()
@@ -101,7 +155,7 @@ impl<'a> Label for Chunk<'a> {
     type Label = ChunkId;
 
     fn label(&self) -> Self::Label {
-        self.id
+        self.id.clone()
     }
 }
 
@@ -126,7 +180,7 @@ impl<'a> crate::common::chunk::Chunk for Chunk<'a> {}
 
 impl<'a> Identifiable<ChunkId> for Chunk<'a> {
     fn get_id(&self) -> ChunkId {
-        self.id
+        self.id.clone()
     }
 }
 
@@ -165,7 +219,9 @@ impl<'a> Edges<ChunkId, ChunkChild> for Chunk<'a> {
 pub struct Origin<'a> {
     #[serde(borrow)]
     pub loc: Cow<'a, str>,
-    pub module_identifier: ModuleIdentifier,
+    /// Rspack leaves it out for entry origins
+    #[serde(default)]
+    pub module_identifier: Option<ModuleIdentifier>,
     #[serde(default)]
     pub module_id: Option<ModuleId>,
     pub module_name: RelativeModulePath<'a>,
@@ -23,18 +23,53 @@ use crate::v5::reason::Reasons;
 use empty_type::{Empty, EmptyType};
 use serde::{Deserialize, Deserializer};
 
-use crate::common::import::{ImportType, SourceText};
-use crate::common::module::{ModuleId, ModuleIdentifier, ModuleName};
+use crate::common::import::{ImportLocation, ImportType, SourceText};
+use crate::common::module::{ModuleId, ModuleIdentifier, ModuleIndex, ModuleName};
 use crate::import::ResolvedModule;
 use crate::module::{IncludedModuleNames, ModuleChunks};
 use meshed::prelude::*;
 
+/// The modules of a compilation, chunk or concatenated module. Read only so
+/// that the index always matches the modules.
 #[derive(Debug, Default)]
 pub struct Modules<'a> {
-    pub modules: Vec<Module<'a>>,
+    modules: Vec<Module<'a>>,
+    index: ModuleIndex,
 }
 
-impl<'a> crate::common::module::Modules<Module<'a>> for Modules<'a> {}
+impl<'a> Modules<'a> {
+    pub fn new(modules: Vec<Module<'a>>) -> Self {
+        let mut index = ModuleIndex::default();
+        for (position, module) in modules.iter().enumerate() {
+            index.insert_module(&module.identifier, position);
+        }
+        for module in modules.iter() {
+            for child in module.modules.iter() {
+                index.insert_concatenated(&child.identifier, &module.identifier);
+            }
+        }
+        Self { modules, index }
+    }
+
+    /// The modules without the modules concatenated into them
+    pub fn iter(&self) -> std::slice::Iter<'_, Module<'a>> {
+        self.modules.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+}
+
+impl<'a> crate::common::module::Modules<Module<'a>> for Modules<'a> {
+    fn top_level(&self) -> Vec<&Module<'a>> {
+        self.modules.iter().collect()
+    }
+}
 
 impl<'de: 'a, 'a> Deserialize<'de> for Modules<'a> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -44,15 +79,15 @@ impl<'de: 'a, 'a> Deserialize<'de> for Modules<'a> {
         let empty_vec: Vec<Empty<Module<'a>>> = Deserialize::deserialize(deserializer)?;
 
         let materialized_vec = empty_vec.into_iter().map(|i| i.resolve()).collect();
-        Ok(Self {
-            modules: materialized_vec,
-        })
+        Ok(Self::new(materialized_vec))
     }
 }
 
 impl<'a> Query<ModuleIdentifier, Module<'a>> for Modules<'a> {
-    fn query(&self, _identifier: &ModuleIdentifier) -> Option<&Module<'a>> {
-        panic!("Query should be called on the module's index not on the modules iterator itself.")
+    /// Returns the module that owns `identifier`. For concatenated modules that
+    /// is the module they were concatenated into.
+    fn query(&self, identifier: &ModuleIdentifier) -> Option<&Module<'a>> {
+        self.index.find(&self.modules, identifier)
     }
 
     fn all(&self) -> Vec<&Module<'a>> {
@@ -66,7 +101,7 @@ impl<'a> Query<ModuleIdentifier, Module<'a>> for Modules<'a> {
         let mut map: HashMap<_, _> = Default::default();
 
         for module in self.modules.iter() {
-            for child in module.modules.modules.iter() {
+            for child in module.modules.iter() {
                 map.insert(child.get_id(), Link::Link(module.get_id()));
             }
             map.insert(module.get_id(), Link::Value(module));
@@ -96,6 +131,12 @@ impl<'a> ExtractData<ModuleChunks> for Module<'a> {
     }
 }
 
+impl<'a> ExtractData<SizeBytes> for Module<'a> {
+    fn extract_data(&self) -> SizeBytes {
+        self.size
+    }
+}
+
 impl<'a> Label for Module<'a> {
     type Label = ModuleName;
 
@@ -104,7 +145,18 @@ impl<'a> Label for Module<'a> {
     }
 }
 
-impl<'a> crate::common::module::Module for Module<'a> {}
+impl<'a> crate::common::module::Module for Module<'a> {
+    fn source(&self) -> Option<&str> {
+        self.source
+            .as_ref()
+            .map(SourceText::as_str)
+            .filter(|source| !source.is_empty())
+    }
+
+    fn concatenated(&self) -> Vec<&Self> {
+        self.modules.all()
+    }
+}
 
 impl<'a> Identifiable<ModuleIdentifier> for Module<'a> {
     fn get_id(&self) -> ModuleIdentifier {
@@ -112,11 +164,11 @@ impl<'a> Identifiable<ModuleIdentifier> for Module<'a> {
     }
 }
 
-impl<'a> Edges<ModuleIdentifier, (ImportType, ResolvedModule)> for Module<'a> {
+impl<'a> Edges<ModuleIdentifier, (ImportType, ResolvedModule, ImportLocation)> for Module<'a> {
     fn next_edge(
         &self,
         previous_edge_index: Option<usize>,
-    ) -> Option<Edge<ModuleIdentifier, (ImportType, ResolvedModule)>> {
+    ) -> Option<Edge<ModuleIdentifier, (ImportType, ResolvedModule, ImportLocation)>> {
         let next_index = previous_edge_index.map(|e| e + 1).unwrap_or_default();
         let reason = self.reasons.get(next_index)?;
         Some(Edge::new(
@@ -125,7 +177,8 @@ impl<'a> Edges<ModuleIdentifier, (ImportType, ResolvedModule)> for Module<'a> {
             next_index,
             (
                 reason.r#type,
-                ResolvedModule(reason.resolved_module.clone()),
+                ResolvedModule(reason.resolved_module().clone()),
+                ImportLocation(reason.loc.as_ref().into()),
             ),
         ))
     }
@@ -135,7 +188,8 @@ impl<'a> Edges<ModuleIdentifier, (ImportType, ResolvedModule)> for Module<'a> {
 #[serde(rename_all = "camelCase")]
 #[empty(bounds = "'a", deserialize)]
 pub struct Module<'a> {
-    // #[serde(borrow)]
+    /// Not reported by rspack
+    #[empty(default)]
     pub assets: Vec<serde_json::Value>,
     /// Indicates that the module went through loaders,
     /// Parsing, and Code Generation
@@ -173,6 +227,7 @@ pub struct Module<'a> {
 }
 
 #[derive(Deserialize, Debug, Default)]
+#[serde(default)]
 pub struct Profile {
     pub building: DurationMillis,
     pub dependencies: DurationMillis,
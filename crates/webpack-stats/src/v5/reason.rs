@@ -66,14 +66,30 @@ impl<'de: 'a, 'a> Deserialize<'de> for Reasons<'a> {
 #[empty(bounds = "'a", deserialize)]
 pub struct Reason<'a> {
     #[serde(borrow)]
-    #[empty(fail_safe)]
+    #[empty(default)]
     pub loc: Cow<'a, str>,
     pub module: RelativeModulePath<'a>,
     pub module_id: Option<ModuleId>,
+    #[empty(default)]
     pub module_name: ModuleName,
+    /// Only reported by webpack 5. See [`Reason::resolved_module`]
+    #[empty(default)]
     pub resolved_module: ModuleName,
     pub module_identifier: ModuleIdentifier,
     pub r#type: ImportType,
     #[empty(fail_safe)]
     pub user_request: ImportString<'a>,
 }
+
+impl<'a> Reason<'a> {
+    /// The module the import resolved to. Versions that do not report it
+    /// fall back to the readable name of the importer, the closest
+    /// equivalent
+    pub fn resolved_module(&self) -> &ModuleName {
+        if self.resolved_module.0.is_empty() {
+            &self.module_name
+        } else {
+            &self.resolved_module
+        }
+    }
+}
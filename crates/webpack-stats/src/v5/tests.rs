@@ -21,6 +21,134 @@ static SOURCE_FILE: &str = include_str!(concat!(
 
 #[test]
 fn full_deserialization() {
-    let value: super::Stats =
-        serde_json::from_str(SOURCE_FILE).expect("Does serde");
+    let value: super::Stats = serde_json::from_str(SOURCE_FILE).expect("Does serde");
+}
+
+/// Same build as `test_projects/v5` with `optimization.chunkIds: 'named'`.
+static NAMED_CHUNKS_SOURCE_FILE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/test_projects/v5-named-chunks/compilation-stats.json"
+));
+
+#[test]
+fn named_chunk_ids() {
+    use crate::chunk::ChunkId;
+    use crate::entry::Entrypoint;
+
+    let value: super::Stats = serde_json::from_str(NAMED_CHUNKS_SOURCE_FILE).expect("Does serde");
+
+    let index = value.entrypoints.values().find(|e| e.name() == "index");
+    assert_eq!(index.unwrap().chunks(), &[ChunkId::from("index")]);
+
+    let entry_two = value
+        .chunks
+        .iter()
+        .find(|chunk| chunk.id == ChunkId::from("entryTwo"))
+        .expect("named chunk exists");
+    assert_eq!(
+        entry_two.children,
+        vec![ChunkId::from("utils_js"), ChunkId::from("module1_js")]
+    );
+}
+
+#[test]
+fn chunk_id_strings_stay_names() {
+    use crate::chunk::ChunkId;
+
+    let id: ChunkId = serde_json::from_str(r#""123""#).unwrap();
+    assert_eq!(id, ChunkId::from("123"));
+    assert_ne!(id, ChunkId::Number(123));
+    assert_eq!("826".parse::<ChunkId>().unwrap(), ChunkId::Number(826));
+    assert_eq!(
+        serde_json::to_string(&ChunkId::from("index")).unwrap(),
+        r#""index""#
+    );
+}
+
+static MULTI_COMPILER_SOURCE_FILE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/test_projects/v5-multi-compiler/compilation-stats.json"
+));
+
+#[test]
+fn multi_compiler_children() {
+    use crate::compilation::Compilation;
+
+    let value: super::Stats = serde_json::from_str(MULTI_COMPILER_SOURCE_FILE).expect("Does serde");
+
+    assert!(value.entrypoints.is_empty());
+    assert_eq!(value.compilation_names(), vec!["client", "server"]);
+
+    let server = value.compilation("server").expect("server compilation");
+    assert!(server.entrypoints.contains_key("entryTwo"));
+    assert!(value.compilation("worker").is_none());
+
+    let client = value
+        .into_compilation("client")
+        .expect("client compilation");
+    assert_eq!(client.name.as_deref(), Some("client"));
+    assert_eq!(client.chunks.len(), 5);
+}
+
+#[test]
+fn chunk_query_uses_index() {
+    use crate::chunk::ChunkId;
+    use meshed::prelude::*;
+
+    let value: super::Stats = serde_json::from_str(SOURCE_FILE).expect("Does serde");
+    for chunk in value.chunks.iter() {
+        let found = value.chunks.query(&chunk.id).expect("chunk is indexed");
+        assert_eq!(found.id, chunk.id);
+    }
+    assert!(value.chunks.query(&ChunkId::Number(404)).is_none());
+}
+
+#[test]
+fn module_query_returns_owning_module() {
+    use meshed::prelude::*;
+
+    let value: super::Stats = serde_json::from_str(SOURCE_FILE).expect("Does serde");
+    let modules = &value.modules;
+    for module in modules.iter() {
+        let found = modules
+            .query(&module.identifier)
+            .expect("module is indexed");
+        assert_eq!(found.identifier, module.identifier);
+    }
+
+    // index.js is only emitted concatenated into `index.js + 1 modules`, while
+    // module2.js is also emitted on its own and resolves to itself.
+    let owner = modules
+        .iter()
+        .find(|module| !module.modules.is_empty())
+        .expect("fixture has a concatenated module");
+    for child in owner.modules.iter() {
+        let found = modules.query(&child.identifier).expect("child is indexed");
+        let standalone = modules
+            .iter()
+            .any(|module| module.identifier == child.identifier);
+        if standalone {
+            assert_eq!(found.identifier, child.identifier);
+        } else {
+            assert_eq!(found.identifier, owner.identifier);
+        }
+    }
+    let missing = crate::module::ModuleIdentifier("./missing.js".into());
+    assert!(modules.query(&missing).is_none());
+}
+
+#[test]
+fn reasons_keep_their_location() {
+    let value: super::Stats = serde_json::from_str(SOURCE_FILE).expect("Does serde");
+    let common = value
+        .modules
+        .iter()
+        .find(|module| module.name.0.as_ref() == "./common.js")
+        .expect("fixture has common.js");
+
+    assert!(common.reasons.iter().all(|reason| !reason.loc.is_empty()));
+    assert!(common
+        .reasons
+        .iter()
+        .any(|reason| reason.loc.as_ref() == "17:0-30"));
 }